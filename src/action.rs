@@ -15,15 +15,21 @@
 //! - [`BindingConfig`] - Configuration container for context-aware action bindings
 //! - [`BindingContextName`] - Named context identifier for organizing input bindings
 //! - [`Action`] - Marker trait for types that can be deserialized from JSON as actions
+//! - [`ActionList`] - Sequence of actions triggered by a single binding
+//! - [`NoOp`] - Built-in action that does nothing
 //! - [`Binding`] - Individual input binding with matcher, action, and optional context switch
+//! - [`BindingMatch`] - A binding matched against an input, with the mouse position if any
 //! - [`InputMatcher`] - Input matching logic for keyboard and mouse events
+//! - [`InputMatcherSet`] - Precompiled set of input matchers for fast lookup
+//! - [`InputMatcherParseError`] - Error returned when a trigger string cannot be parsed
 use std::path::Path;
 
-use crate::binding::ContextualBindings;
+use crate::binding::{ContextualBindings, binding_json_schema, contextual_bindings_json_schema};
 use crate::json::LoadJsonError;
+use crate::legend::Legend;
 
 pub use crate::binding::Binding;
-pub use crate::matcher::InputMatcher;
+pub use crate::matcher::{InputMatcher, InputMatcherParseError, InputMatcherSet};
 
 /// Marker trait for types that can be deserialized from JSON as action definitions.
 pub trait Action:
@@ -31,13 +37,129 @@ pub trait Action:
 {
 }
 
+/// A sequence of actions that are executed in order by a single binding.
+///
+/// In JSON, this is written either as an array of actions or as a single action
+/// (which is treated as a one-element list). Since an array is always interpreted as
+/// a sequence, `A` itself should not be parsed from a JSON array.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), nojson::JsonParseError> {
+/// use mame::action::{ActionList, NoOp};
+///
+/// let json = nojson::RawJson::parse("[null, null]")?;
+/// let actions = ActionList::<NoOp>::try_from(json.value())?;
+/// assert_eq!(actions.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionList<A>(Vec<A>);
+
+impl<A> ActionList<A> {
+    /// Creates a new action list.
+    pub fn new(actions: Vec<A>) -> Self {
+        Self(actions)
+    }
+
+    /// Returns the actions in execution order.
+    pub fn get(&self) -> &[A] {
+        &self.0
+    }
+
+    /// Returns the number of actions.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the list contains no actions.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the actions in execution order.
+    pub fn iter(&self) -> std::slice::Iter<'_, A> {
+        self.0.iter()
+    }
+}
+
+impl<A> IntoIterator for ActionList<A> {
+    type Item = A;
+    type IntoIter = std::vec::IntoIter<A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, A> IntoIterator for &'a ActionList<A> {
+    type Item = &'a A;
+    type IntoIter = std::slice::Iter<'a, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<A: Action> Action for ActionList<A> {}
+
+impl<'text, 'raw, A: Action> TryFrom<nojson::RawJsonValue<'text, 'raw>> for ActionList<A> {
+    type Error = nojson::JsonParseError;
+
+    fn try_from(value: nojson::RawJsonValue<'text, 'raw>) -> Result<Self, Self::Error> {
+        if let Ok(array) = value.to_array() {
+            array.map(A::try_from).collect::<Result<_, _>>().map(Self)
+        } else {
+            A::try_from(value).map(|action| Self(vec![action]))
+        }
+    }
+}
+
+impl<A: nojson::DisplayJson> nojson::DisplayJson for ActionList<A> {
+    fn fmt(&self, f: &mut nojson::JsonFormatter<'_, '_>) -> std::fmt::Result {
+        nojson::DisplayJson::fmt(&self.0, f)
+    }
+}
+
+/// An action that does nothing, written as `null` in JSON.
+///
+/// This is useful as a placeholder action, or as the action type for configurations
+/// that only use bindings to switch contexts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NoOp;
+
+impl Action for NoOp {}
+
+impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for NoOp {
+    type Error = nojson::JsonParseError;
+
+    fn try_from(value: nojson::RawJsonValue<'text, 'raw>) -> Result<Self, Self::Error> {
+        if matches!(value.kind(), nojson::JsonValueKind::Null) {
+            Ok(Self)
+        } else {
+            Err(value.invalid("expected null"))
+        }
+    }
+}
+
+impl nojson::DisplayJson for NoOp {
+    fn fmt(&self, f: &mut nojson::JsonFormatter<'_, '_>) -> std::fmt::Result {
+        nojson::DisplayJson::fmt(&None::<bool>, f)
+    }
+}
+
 /// A configuration container for context-aware action bindings.
 ///
 /// Holds multiple input bindings organized by context, with an optional setup action
 /// and setup context for initialization. This is a stateless configuration structure
 /// that provides read-only access to binding definitions loaded from JSON/JSONC files.
 /// Supports both keyboard and mouse input event definitions.
-#[derive(Debug)]
+///
+/// When the action type implements [`nojson::DisplayJson`], the configuration can be
+/// serialized back to JSON in the same format that it is loaded from.
+#[derive(Debug, PartialEq)]
 pub struct BindingConfig<A> {
     initial_context: BindingContextName,
     setup_action: Option<A>,
@@ -84,6 +206,71 @@ impl<A: Action> BindingConfig<A> {
             .iter()
             .map(|(k, v)| (k, &v[..]))
     }
+
+    /// Returns the first binding in the specified context that matches the given input.
+    ///
+    /// For mouse input, the returned [`BindingMatch`] also carries the position of the event.
+    pub fn find_binding(
+        &self,
+        context: &BindingContextName,
+        input: tuinix::TerminalInput,
+    ) -> Option<BindingMatch<'_, A>> {
+        let binding = self
+            .get_bindings(context)?
+            .iter()
+            .find(|b| b.matches(input))?;
+        let mouse_position = match input {
+            tuinix::TerminalInput::Key(_) => None,
+            tuinix::TerminalInput::Mouse(mouse) => Some(mouse.position),
+        };
+        Some(BindingMatch {
+            binding,
+            mouse_position,
+        })
+    }
+
+    /// Returns the bindings that can never be matched, as `(context, index)` pairs.
+    ///
+    /// Because the first matching binding wins, a binding is unreachable if every one of
+    /// its triggers is covered by triggers of earlier bindings in the same context
+    /// (e.g., `"a"` after `"<PRINTABLE>"`). Bindings without triggers, or with only
+    /// `"<NONE>"` triggers, are intentionally inactive and are not reported.
+    pub fn lint(&self) -> Vec<(&BindingContextName, usize)> {
+        let mut unreachable = Vec::new();
+        for (context, bindings) in self.all_bindings() {
+            let mut seen: Vec<InputMatcher> = Vec::new();
+            for (i, binding) in bindings.iter().enumerate() {
+                let mut triggers = binding
+                    .triggers
+                    .iter()
+                    .filter(|t| **t != InputMatcher::Never)
+                    .peekable();
+                if triggers.peek().is_some()
+                    && triggers.all(|t| seen.iter().any(|s| t.is_covered_by(*s)))
+                {
+                    unreachable.push((context, i));
+                }
+                seen.extend(binding.triggers.iter().copied());
+            }
+        }
+        unreachable
+    }
+
+    /// Creates a [`Legend`] listing the input bindings of the specified context.
+    ///
+    /// Each item is the binding's label, or its triggers if the binding has no label.
+    /// Bindings with neither a label nor an action are skipped.
+    pub fn legend<'a>(&self, context: &BindingContextName, title: &'a str) -> Legend<'a> {
+        let bindings = self.get_bindings(context).unwrap_or_default();
+        let items = bindings
+            .iter()
+            .filter(|b| b.label.is_some() || b.action.is_some())
+            .map(|b| match &b.label {
+                Some(label) => label.clone(),
+                None => b.triggers_display().to_string(),
+            });
+        Legend::new(title, items)
+    }
 }
 
 impl<'text, 'raw, A: Action> TryFrom<nojson::RawJsonValue<'text, 'raw>> for BindingConfig<A> {
@@ -99,6 +286,102 @@ impl<'text, 'raw, A: Action> TryFrom<nojson::RawJsonValue<'text, 'raw>> for Bind
     }
 }
 
+/// JSON Schema fragment accepted by the `setup` part of the [`BindingConfig`] parser.
+fn setup_json_schema() -> impl nojson::DisplayJson {
+    nojson::json(|f| {
+        f.object(|f| {
+            f.member("type", "object")?;
+            f.member("required", ["context"])?;
+            f.member(
+                "properties",
+                nojson::json(|f| {
+                    f.object(|f| {
+                        f.member(
+                            "context",
+                            nojson::json(|f| f.object(|f| f.member("$ref", "#/$defs/context"))),
+                        )?;
+                        f.member("action", nojson::json(|f| f.object(|_| Ok(()))))
+                    })
+                }),
+            )
+        })
+    })
+}
+
+impl<A: nojson::DisplayJson> nojson::DisplayJson for BindingConfig<A> {
+    fn fmt(&self, f: &mut nojson::JsonFormatter<'_, '_>) -> std::fmt::Result {
+        f.object(|f| {
+            f.member(
+                "setup",
+                nojson::json(|f| {
+                    f.object(|f| {
+                        f.member("context", &self.initial_context)?;
+                        if let Some(action) = &self.setup_action {
+                            f.member("action", action)?;
+                        }
+                        Ok(())
+                    })
+                }),
+            )?;
+            f.member("bindings", &self.contextual_bindings)
+        })
+    }
+}
+
+/// Returns a JSON Schema (draft 2020-12) describing the [`BindingConfig`] file format.
+///
+/// The schema covers the `setup` and `bindings` structure and the trigger grammar
+/// accepted by [`InputMatcher`]. Actions are application-defined, so any JSON value
+/// is accepted where an action appears. The schema describes the configuration after
+/// preprocessing; `{"env!": ...}` objects and `${NAME}` references are not taken
+/// into account.
+///
+/// This can be written to a file and referenced from editors to validate
+/// configuration files while they are being edited.
+pub fn binding_config_json_schema() -> String {
+    nojson::json(|f| {
+        f.set_indent_size(2);
+        f.set_spacing(true);
+        f.object(|f| {
+            f.member("$schema", "https://json-schema.org/draft/2020-12/schema")?;
+            f.member("type", "object")?;
+            f.member("required", ["setup", "bindings"])?;
+            f.member(
+                "properties",
+                nojson::json(|f| {
+                    f.object(|f| {
+                        f.member("setup", setup_json_schema())?;
+                        f.member("bindings", contextual_bindings_json_schema())
+                    })
+                }),
+            )?;
+            f.member(
+                "$defs",
+                nojson::json(|f| {
+                    f.object(|f| {
+                        f.member("binding", binding_json_schema())?;
+                        f.member(
+                            "context",
+                            nojson::json(|f| f.object(|f| f.member("type", "string"))),
+                        )
+                    })
+                }),
+            )
+        })
+    })
+    .to_string()
+}
+
+/// A binding that matched an input, returned by [`BindingConfig::find_binding()`].
+#[derive(Debug)]
+pub struct BindingMatch<'a, A> {
+    /// The matched binding
+    pub binding: &'a Binding<A>,
+
+    /// Position of the mouse event that triggered the binding (`None` for keyboard input)
+    pub mouse_position: Option<tuinix::TerminalPosition>,
+}
+
 /// A named context identifier for organizing input bindings.
 ///
 /// Contexts allow grouping related input bindings together. Each context
@@ -135,3 +418,280 @@ impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for BindingContextN
         Ok(Self(name))
     }
 }
+
+impl nojson::DisplayJson for BindingContextName {
+    fn fmt(&self, f: &mut nojson::JsonFormatter<'_, '_>) -> std::fmt::Result {
+        f.string(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct TestAction(String);
+
+    impl Action for TestAction {}
+
+    impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for TestAction {
+        type Error = nojson::JsonParseError;
+
+        fn try_from(value: nojson::RawJsonValue<'text, 'raw>) -> Result<Self, Self::Error> {
+            Ok(Self(value.try_into()?))
+        }
+    }
+
+    impl nojson::DisplayJson for TestAction {
+        fn fmt(&self, f: &mut nojson::JsonFormatter<'_, '_>) -> std::fmt::Result {
+            f.string(&self.0)
+        }
+    }
+
+    #[test]
+    fn test_legend() -> Result<(), LoadJsonError> {
+        let config = BindingConfig::<TestAction>::load_from_str(
+            "test",
+            r#"{
+              "setup": {"context": "main"},
+              "bindings": {
+                "main": [
+                  {"triggers": ["C-s"], "label": "Save", "action": "save"},
+                  {"triggers": ["q", "<ESCAPE>"], "action": "quit"},
+                  {"triggers": ["x"]}
+                ]
+              }
+            }"#,
+        )?;
+        let legend = config.legend(config.initial_context(), "Keys");
+        assert_eq!(legend.items(), ["Save", "q/<ESCAPE>"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_context_switch_target() {
+        let config = |target: &str| {
+            let text = format!(
+                r#"{{
+                  "setup": {{"context": "main"}},
+                  "bindings": {{
+                    "main": [{{"triggers": ["i"], "context": "{target}"}}],
+                    "insert": [{{"triggers": ["<ESCAPE>"], "context": "main"}}]
+                  }}
+                }}"#
+            );
+            BindingConfig::<TestAction>::load_from_str("test", &text)
+        };
+
+        let valid = config("insert").expect("valid config");
+        let bindings = valid.get_bindings(valid.initial_context()).expect("main");
+        assert_eq!(bindings[0].context, Some(BindingContextName::new("insert")));
+
+        let error = config("insret").expect_err("undefined context");
+        assert_eq!(
+            error.offending_line().map(str::trim),
+            Some(r#""main": [{"triggers": ["i"], "context": "insret"}],"#)
+        );
+    }
+
+    #[test]
+    fn test_lint() -> Result<(), LoadJsonError> {
+        let config = BindingConfig::<TestAction>::load_from_str(
+            "test",
+            r#"{
+              "setup": {"context": "main"},
+              "bindings": {
+                "main": [
+                  {"triggers": ["<PRINTABLE>", "<ENTER>"]},
+                  {"triggers": ["a"]},
+                  {"triggers": ["C-a"]},
+                  {"triggers": ["<ENTER>", "b"]},
+                  {"triggers": ["<NONE>"]}
+                ],
+                "clean": [
+                  {"triggers": ["a"]},
+                  {"triggers": ["<PRINTABLE>"]},
+                  {"label": "no triggers"}
+                ]
+              }
+            }"#,
+        )?;
+        let main = BindingContextName::new("main");
+        assert_eq!(config.lint(), [(&main, 1), (&main, 3)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_binding() -> Result<(), LoadJsonError> {
+        let config = BindingConfig::<TestAction>::load_from_str(
+            "test",
+            r#"{
+              "setup": {"context": "main"},
+              "bindings": {
+                "main": [
+                  {"triggers": ["<LEFTCLICK>"], "label": "Click"},
+                  {"triggers": ["<PRINTABLE>"], "label": "Insert"},
+                  {"triggers": ["x"], "label": "Unreachable"}
+                ]
+              }
+            }"#,
+        )?;
+        let key = tuinix::KeyInput {
+            ctrl: false,
+            alt: false,
+            code: tuinix::KeyCode::Char('x'),
+        };
+        let m = config
+            .find_binding(config.initial_context(), tuinix::TerminalInput::Key(key))
+            .expect("matched");
+        assert_eq!(m.binding.label.as_deref(), Some("Insert"));
+        assert_eq!(m.mouse_position, None);
+
+        let position = tuinix::TerminalPosition::row_col(3, 7);
+        let mouse = tuinix::MouseInput {
+            event: tuinix::MouseEvent::LeftPress,
+            position,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        };
+        let m = config
+            .find_binding(
+                config.initial_context(),
+                tuinix::TerminalInput::Mouse(mouse),
+            )
+            .expect("matched");
+        assert_eq!(m.binding.label.as_deref(), Some("Click"));
+        assert_eq!(m.mouse_position, Some(position));
+        assert_eq!(m.mouse_position.map(|p| (p.row, p.col)), Some((3, 7)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_binding_config_json_schema() -> Result<(), nojson::JsonParseError> {
+        let schema = binding_config_json_schema();
+        let json = nojson::RawJson::parse(&schema)?;
+        let root = json.value();
+
+        let required: Vec<String> = root.to_member("required")?.required()?.try_into()?;
+        assert_eq!(required, ["setup", "bindings"]);
+
+        let pattern: String = root
+            .to_member("$defs")?
+            .required()?
+            .to_member("binding")?
+            .required()?
+            .to_member("properties")?
+            .required()?
+            .to_member("triggers")?
+            .required()?
+            .to_member("items")?
+            .required()?
+            .to_member("pattern")?
+            .required()?
+            .try_into()?;
+        assert_eq!(pattern, crate::matcher::trigger_pattern());
+
+        // The described members are exactly those read by the parsers
+        let member_names = |path: &[&str]| -> Result<Vec<String>, nojson::JsonParseError> {
+            let mut value = root;
+            for name in path {
+                value = value.to_member(name)?.required()?;
+            }
+            value.to_object()?.map(|(k, _)| k.try_into()).collect()
+        };
+        assert_eq!(member_names(&["$defs", "binding"])?, ["type", "properties"]);
+        assert_eq!(
+            member_names(&["$defs", "binding", "properties"])?,
+            ["triggers", "label", "action", "context"]
+        );
+        assert_eq!(
+            member_names(&["properties", "setup"])?,
+            ["type", "required", "properties"]
+        );
+        assert_eq!(
+            member_names(&["properties", "setup", "properties"])?,
+            ["context", "action"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_round_trip() -> Result<(), LoadJsonError> {
+        let config = BindingConfig::<TestAction>::load_from_str(
+            "test",
+            r#"{
+              "setup": {"context": "main", "action": "init"},
+              "bindings": {
+                "main": [
+                  {"triggers": ["Ctrl-s", "0x13"], "label": "Save", "action": "save"},
+                  {"triggers": ["i"], "context": "insert"},
+                  {"label": "Help"}
+                ],
+                "insert": [
+                  {"triggers": ["<ESCAPE>", "<LEFTCLICK>"], "context": "main"},
+                  {"triggers": ["<PRINTABLE>"], "action": "insert"}
+                ]
+              }
+            }"#,
+        )?;
+
+        let text = nojson::Json(&config).to_string();
+        let reparsed = BindingConfig::<TestAction>::load_from_str("reparsed", &text)?;
+        assert_eq!(reparsed, config);
+        assert_eq!(nojson::Json(&reparsed).to_string(), text);
+        Ok(())
+    }
+
+    #[test]
+    fn test_action_list() -> Result<(), LoadJsonError> {
+        let config = BindingConfig::<ActionList<TestAction>>::load_from_str(
+            "test",
+            r#"{
+              "setup": {"context": "main", "action": "init"},
+              "bindings": {
+                "main": [
+                  {"triggers": ["C-s"], "action": ["format", "save", "close"]},
+                  {"triggers": ["q"], "action": []}
+                ]
+              }
+            }"#,
+        )?;
+
+        let setup = config.setup_action().expect("setup action");
+        assert_eq!(setup.get(), [TestAction("init".to_owned())]);
+
+        let bindings = config.get_bindings(config.initial_context()).expect("main");
+        let actions = bindings[0].action.as_ref().expect("action");
+        let names = actions.iter().map(|a| a.0.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["format", "save", "close"]);
+        assert!(bindings[1].action.as_ref().is_some_and(|a| a.is_empty()));
+
+        assert!(
+            BindingConfig::<NoOp>::load_from_str(
+                "test",
+                r#"{"setup": {"context": "main"}, "bindings": {"main": [{"action": 1}]}}"#,
+            )
+            .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_binding_describe() -> Result<(), InputMatcherParseError> {
+        let mut binding = Binding::<TestAction> {
+            triggers: vec!["C-s".parse()?],
+            label: Some("Save file".to_owned()),
+            action: None,
+            context: None,
+        };
+        assert_eq!(binding.describe(), "C-s  Save file");
+
+        binding.triggers.push("<ENTER>".parse()?);
+        assert_eq!(binding.describe(), "C-s/<ENTER>  Save file");
+
+        binding.label = None;
+        assert_eq!(binding.describe(), "C-s/<ENTER>");
+        Ok(())
+    }
+}
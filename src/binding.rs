@@ -1,9 +1,9 @@
 use std::collections::BTreeMap;
 
 use crate::action::{Action, BindingContextName};
-use crate::matcher::InputMatcher;
+use crate::matcher::{InputMatcher, trigger_pattern};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) struct ContextualBindings<A> {
     pub(crate) bindings: BTreeMap<BindingContextName, Vec<Binding<A>>>,
 }
@@ -25,8 +25,40 @@ impl<'text, 'raw, A: Action> TryFrom<nojson::RawJsonValue<'text, 'raw>> for Cont
     }
 }
 
+/// JSON Schema fragment accepted by the [`ContextualBindings`] parser.
+pub(crate) fn contextual_bindings_json_schema() -> impl nojson::DisplayJson {
+    nojson::json(|f| {
+        f.object(|f| {
+            f.member("type", "object")?;
+            f.member(
+                "additionalProperties",
+                nojson::json(|f| {
+                    f.object(|f| {
+                        f.member("type", "array")?;
+                        f.member(
+                            "items",
+                            nojson::json(|f| f.object(|f| f.member("$ref", "#/$defs/binding"))),
+                        )
+                    })
+                }),
+            )
+        })
+    })
+}
+
+impl<A: nojson::DisplayJson> nojson::DisplayJson for ContextualBindings<A> {
+    fn fmt(&self, f: &mut nojson::JsonFormatter<'_, '_>) -> std::fmt::Result {
+        f.object(|f| {
+            for (context, bindings) in &self.bindings {
+                f.member(context.get(), bindings)?;
+            }
+            Ok(())
+        })
+    }
+}
+
 /// A single input binding that maps terminal input patterns to actions within a context.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Binding<A> {
     /// Input patterns that trigger this binding (keyboard keys, mouse events, etc.)
     pub triggers: Vec<InputMatcher>,
@@ -48,6 +80,35 @@ impl<A: Action> Binding<A> {
     pub fn matches(&self, input: tuinix::TerminalInput) -> bool {
         self.triggers.iter().any(|t| t.matches(input))
     }
+
+    /// Returns a one-line human-readable description of this binding.
+    ///
+    /// The description consists of the triggers separated by `/`, followed by
+    /// the label (if any), e.g. `C-s/C-x  Save file`.
+    pub fn describe(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{}  {label}", self.triggers_display()),
+            None => self.triggers_display().to_string(),
+        }
+    }
+
+    pub(crate) fn triggers_display(&self) -> impl '_ + std::fmt::Display {
+        Triggers(&self.triggers)
+    }
+}
+
+struct Triggers<'a>(&'a [InputMatcher]);
+
+impl std::fmt::Display for Triggers<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, trigger) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "/")?;
+            }
+            write!(f, "{trigger}")?;
+        }
+        Ok(())
+    }
 }
 
 impl<'text, 'raw, A: Action> TryFrom<nojson::RawJsonValue<'text, 'raw>> for Binding<A> {
@@ -65,3 +126,64 @@ impl<'text, 'raw, A: Action> TryFrom<nojson::RawJsonValue<'text, 'raw>> for Bind
         })
     }
 }
+
+/// JSON Schema fragment accepted by the [`Binding`] parser.
+pub(crate) fn binding_json_schema() -> impl nojson::DisplayJson {
+    let trigger_pattern = trigger_pattern();
+    nojson::json(move |f| {
+        f.object(|f| {
+            f.member("type", "object")?;
+            f.member(
+                "properties",
+                nojson::json(|f| {
+                    f.object(|f| {
+                        f.member(
+                            "triggers",
+                            nojson::json(|f| {
+                                f.object(|f| {
+                                    f.member("type", "array")?;
+                                    f.member(
+                                        "items",
+                                        nojson::json(|f| {
+                                            f.object(|f| {
+                                                f.member("type", "string")?;
+                                                f.member("pattern", &trigger_pattern)
+                                            })
+                                        }),
+                                    )
+                                })
+                            }),
+                        )?;
+                        f.member(
+                            "label",
+                            nojson::json(|f| f.object(|f| f.member("type", "string"))),
+                        )?;
+                        f.member("action", nojson::json(|f| f.object(|_| Ok(()))))?;
+                        f.member(
+                            "context",
+                            nojson::json(|f| f.object(|f| f.member("$ref", "#/$defs/context"))),
+                        )
+                    })
+                }),
+            )
+        })
+    })
+}
+
+impl<A: nojson::DisplayJson> nojson::DisplayJson for Binding<A> {
+    fn fmt(&self, f: &mut nojson::JsonFormatter<'_, '_>) -> std::fmt::Result {
+        f.object(|f| {
+            f.member("triggers", &self.triggers)?;
+            if let Some(label) = &self.label {
+                f.member("label", label)?;
+            }
+            if let Some(action) = &self.action {
+                f.member("action", action)?;
+            }
+            if let Some(context) = &self.context {
+                f.member("context", context)?;
+            }
+            Ok(())
+        })
+    }
+}
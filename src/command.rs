@@ -4,10 +4,11 @@
 //! with fine-grained control over stdin, stdout, and stderr. Commands can be configured
 //! to read input from text or files, and write output to files with various options.
 use std::collections::BTreeMap;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
 
-use crate::io_error;
 use crate::json;
 
 /// Configuration for executing an external command with customizable I/O handling.
@@ -22,6 +23,17 @@ pub struct ExternalCommand {
     /// Environment variables to set for the command execution
     pub envs: BTreeMap<String, String>,
 
+    /// Working directory for the command execution
+    ///
+    /// When set, relative stdin/stdout/stderr file paths are also resolved against this directory.
+    pub current_dir: Option<PathBuf>,
+
+    /// Whether to expand glob patterns in `args` when the command is executed
+    /// (JSON: `"expand-globs"`, default `false`)
+    ///
+    /// See [`ExternalCommand::expanded_args()`] for the supported syntax.
+    pub expand_globs: bool,
+
     /// Configuration for handling stdin input
     pub stdin: CommandInput,
 
@@ -33,55 +45,110 @@ pub struct ExternalCommand {
 }
 
 impl ExternalCommand {
+    /// Creates a builder for a command that runs the given executable.
+    pub fn builder<P: Into<PathBuf>>(command: P) -> ExternalCommandBuilder {
+        ExternalCommandBuilder::new(command)
+    }
+
     /// Executes the external command with configured I/O handling.
     ///
     /// Spawns the process with the specified arguments and environment variables,
     /// handles stdin input, waits for completion, and processes stdout/stderr
     /// according to the configured output settings.
     ///
-    /// Returns the complete process output including exit status and captured streams.
-    pub fn execute(&self) -> std::io::Result<std::process::Output> {
+    /// Returns the exit status along with any stdout/stderr text captured via
+    /// [`CommandOutput::Capture`].
+    ///
+    /// This is equivalent to [`ExternalCommand::spawn()`] followed by [`RunningCommand::wait()`].
+    pub fn execute(&self) -> Result<ExternalCommandOutput, CommandError> {
+        self.spawn()?.wait()
+    }
+
+    /// Starts the external command without waiting for it to finish.
+    ///
+    /// Stdin is written and stdout/stderr are collected by background threads, so the
+    /// caller is never blocked on the command's I/O. Use [`RunningCommand::try_wait()`]
+    /// or the handle's file descriptor to poll for completion and [`RunningCommand::wait()`]
+    /// to obtain the output.
+    pub fn spawn(&self) -> Result<RunningCommand, CommandError> {
         let mut cmd = std::process::Command::new(&self.command);
-        for arg in &self.args {
+        for arg in self.expanded_args() {
             cmd.arg(arg);
         }
         for (k, v) in &self.envs {
             cmd.env(k, v);
         }
+        if let Some(dir) = &self.current_dir {
+            cmd.current_dir(dir);
+        }
         cmd.stdin(std::process::Stdio::piped());
         cmd.stdout(std::process::Stdio::piped());
         cmd.stderr(std::process::Stdio::piped());
 
-        let mut child = cmd.spawn().map_err(|e| {
-            let name = self.command.display();
-            io_error(e, &format!("failed to execute command '{name}'"))
-        })?;
+        let spawn_error = |error| CommandError::Spawn {
+            command: self.command.clone(),
+            error,
+        };
+        let (ready, ready_writer) = std::io::pipe().map_err(spawn_error)?;
+        let mut child = cmd.spawn().map_err(spawn_error)?;
 
-        self.stdin.handle_input(child.stdin.take()).map_err(|e| {
-            let name = self.command.display();
-            io_error(e, &format!("failed to write stdin to command '{name}'"))
-        })?;
-        let output = child.wait_with_output().map_err(|e| {
-            let name = self.command.display();
-            io_error(e, &format!("failed to wait for command '{name}'"))
-        })?;
+        // Each I/O thread holds the write end until it finishes, so `ready` reaches EOF
+        // once all of them are done
+        let ready_writer = Arc::new(ready_writer);
+        let stdin = child.stdin.take().map(|writer| {
+            let input = self.stdin.clone();
+            let base_dir = self.current_dir.clone();
+            let ready_writer = Arc::clone(&ready_writer);
+            std::thread::spawn(move || {
+                let _ready_writer = ready_writer;
+                input.handle_input(Some(writer), base_dir.as_deref())
+            })
+        });
+        let stdout = spawn_reader(child.stdout.take(), Arc::clone(&ready_writer));
+        let stderr = spawn_reader(child.stderr.take(), ready_writer);
 
-        let success = output.status.success();
+        Ok(RunningCommand {
+            command: self.clone(),
+            child,
+            stdin,
+            stdout,
+            stderr,
+            ready,
+        })
+    }
 
-        self.stdout
-            .handle_output(&output.stdout, success)
-            .map_err(|e| {
-                let name = self.command.display();
-                io_error(e, &format!("failed to handle stdout from command '{name}'"))
-            })?;
-        self.stderr
-            .handle_output(&output.stderr, success)
-            .map_err(|e| {
-                let name = self.command.display();
-                io_error(e, &format!("failed to handle stderr from command '{name}'"))
-            })?;
+    /// Executes the command after substituting `{name}` placeholders with `vars`.
+    ///
+    /// Placeholders in `args` and in [`CommandInput::Text`] are replaced, and unknown
+    /// placeholders are left as-is. See [`ExternalCommand::with_vars()`] for the syntax.
+    pub fn execute_with(
+        &self,
+        vars: &BTreeMap<String, String>,
+    ) -> Result<ExternalCommandOutput, CommandError> {
+        self.with_vars(vars, PlaceholderPolicy::Keep)
+            .expect("keeping unknown placeholders never fails")
+            .execute()
+    }
 
-        Ok(output)
+    /// Returns a copy of this command with `{name}` placeholders substituted with `vars`.
+    ///
+    /// Placeholders are replaced in `args` and in [`CommandInput::Text`]. A placeholder name
+    /// consists of ASCII alphanumerics, `_` and `-`; other text in braces is left untouched.
+    /// `{{` produces a literal `{`. Placeholders whose name is not in `vars` are handled
+    /// according to `policy`.
+    pub fn with_vars(
+        &self,
+        vars: &BTreeMap<String, String>,
+        policy: PlaceholderPolicy,
+    ) -> Result<Self, UnknownPlaceholderError> {
+        let mut command = self.clone();
+        for arg in &mut command.args {
+            *arg = substitute_placeholders(arg, vars, policy)?;
+        }
+        if let CommandInput::Text { text } = &mut command.stdin {
+            *text = substitute_placeholders(text, vars, policy)?;
+        }
+        Ok(command)
     }
 
     /// Returns a command line representation that combines the command and args fields for display purposes.
@@ -91,12 +158,108 @@ impl ExternalCommand {
             args: &self.args,
         }
     }
+
+    /// Returns the arguments that are passed to the command when it is executed.
+    ///
+    /// If [`ExternalCommand::expand_globs`] is `false`, this is the same as `args`.
+    /// Otherwise, arguments containing glob patterns are expanded to the sorted list of
+    /// matching file names, relative to the working directory:
+    /// - `*` matches any sequence of characters, and `?` matches a single character
+    /// - `[abc]`, `[a-z]`, and `[!abc]` match a single character in (or not in) the set
+    /// - Only the last path component may contain patterns (e.g., `src/*.rs`)
+    /// - Names starting with `.` are matched only if the pattern also starts with `.`
+    ///
+    /// Unlike some shells, a pattern that matches nothing is passed through unchanged.
+    pub fn expanded_args(&self) -> Vec<String> {
+        if !self.expand_globs {
+            return self.args.clone();
+        }
+        let base_dir = self.current_dir.as_deref();
+        self.args
+            .iter()
+            .flat_map(|arg| {
+                let matches = expand_glob(arg, base_dir);
+                if matches.is_empty() {
+                    vec![arg.clone()]
+                } else {
+                    matches
+                }
+            })
+            .collect()
+    }
+
+    /// Describes what executing this command would do, without spawning anything.
+    ///
+    /// File paths in the stdin/stdout/stderr settings are resolved against the
+    /// working directory, and glob patterns in the arguments are expanded (see
+    /// [`ExternalCommand::expanded_args()`]), as they would be when the command is executed.
+    pub fn describe(&self) -> ExternalCommandDescription {
+        let base_dir = self.current_dir.as_deref();
+        let args = self.expanded_args();
+        ExternalCommandDescription {
+            command_line: CommandLine {
+                command: &self.command,
+                args: &args,
+            }
+            .to_string(),
+            envs: self
+                .envs
+                .iter()
+                .map(|(k, v)| format!("{k}={}", QuotedArg(v)))
+                .collect(),
+            current_dir: self.current_dir.clone(),
+            stdin: self.stdin.describe(base_dir),
+            stdout: self.stdout.describe(base_dir),
+            stderr: self.stderr.describe(base_dir),
+        }
+    }
+}
+
+/// Human-readable description of an [`ExternalCommand`], returned by
+/// [`ExternalCommand::describe()`].
+///
+/// The [`Display`](std::fmt::Display) implementation renders one `name: value` line per item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalCommandDescription {
+    /// Command line as executed, with arguments quoted as in [`ExternalCommand::command_line()`]
+    pub command_line: String,
+
+    /// Environment variable assignments in `NAME=VALUE` form
+    pub envs: Vec<String>,
+
+    /// Working directory, if set
+    pub current_dir: Option<PathBuf>,
+
+    /// Summary of the stdin source (e.g., `text "hello"`)
+    pub stdin: String,
+
+    /// Summary of how stdout is handled (e.g., `file out.log (append)`)
+    pub stdout: String,
+
+    /// Summary of how stderr is handled
+    pub stderr: String,
+}
+
+impl std::fmt::Display for ExternalCommandDescription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "command: {}", self.command_line)?;
+        for env in &self.envs {
+            writeln!(f, "env: {env}")?;
+        }
+        if let Some(dir) = &self.current_dir {
+            writeln!(f, "cwd: {}", dir.display())?;
+        }
+        writeln!(f, "stdin: {}", self.stdin)?;
+        writeln!(f, "stdout: {}", self.stdout)?;
+        writeln!(f, "stderr: {}", self.stderr)
+    }
 }
 
 impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for ExternalCommand {
     type Error = nojson::JsonParseError;
 
     fn try_from(value: nojson::RawJsonValue<'text, 'raw>) -> Result<Self, Self::Error> {
+        let settings = CommandSettings::try_from(value)?;
         Ok(Self {
             command: json::parse_from_flattened_string(value.to_member("command")?.required()?)?,
             args: value
@@ -107,6 +270,61 @@ impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for ExternalCommand
                         .collect()
                 })?
                 .unwrap_or_default(),
+            envs: settings.envs,
+            current_dir: settings.current_dir,
+            expand_globs: value
+                .to_member("expand-globs")?
+                .map(bool::try_from)?
+                .unwrap_or_default(),
+            stdin: settings.stdin,
+            stdout: settings.stdout,
+            stderr: settings.stderr,
+        })
+    }
+}
+
+/// JSON members shared by [`ExternalCommand`] and [`ShellCommand`].
+struct CommandSettings {
+    envs: BTreeMap<String, String>,
+    current_dir: Option<PathBuf>,
+    stdin: CommandInput,
+    stdout: CommandOutput,
+    stderr: CommandOutput,
+}
+
+impl CommandSettings {
+    fn fmt_members(
+        f: &mut nojson::JsonObjectFormatter,
+        envs: &BTreeMap<String, String>,
+        current_dir: Option<&Path>,
+        stdin: &CommandInput,
+        stdout: &CommandOutput,
+        stderr: &CommandOutput,
+    ) -> std::fmt::Result {
+        if !envs.is_empty() {
+            f.member("envs", envs)?;
+        }
+        if let Some(dir) = current_dir {
+            f.member("cwd", json_path(dir)?)?;
+        }
+        if !matches!(stdin, CommandInput::Null) {
+            f.member("stdin", stdin)?;
+        }
+        if !matches!(stdout, CommandOutput::Null) {
+            f.member("stdout", stdout)?;
+        }
+        if !matches!(stderr, CommandOutput::Null) {
+            f.member("stderr", stderr)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for CommandSettings {
+    type Error = nojson::JsonParseError;
+
+    fn try_from(value: nojson::RawJsonValue<'text, 'raw>) -> Result<Self, Self::Error> {
+        Ok(Self {
             envs: value
                 .to_member("envs")?
                 .map(|v| {
@@ -115,13 +333,21 @@ impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for ExternalCommand
                         .collect()
                 })?
                 .unwrap_or_default(),
+            current_dir: value
+                .to_member("cwd")?
+                .map(json::parse_from_flattened_string)?,
             stdin: value
                 .to_member("stdin")?
                 .map(TryFrom::try_from)?
                 .unwrap_or_default(),
             stdout: value
                 .to_member("stdout")?
-                .map(TryFrom::try_from)?
+                .map(|v| match CommandOutput::try_from(v)? {
+                    CommandOutput::MergeWithStdout => {
+                        Err(v.invalid("merge-with-stdout is only allowed for stderr"))
+                    }
+                    output => Ok(output),
+                })?
                 .unwrap_or_default(),
             stderr: value
                 .to_member("stderr")?
@@ -131,6 +357,580 @@ impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for ExternalCommand
     }
 }
 
+/// Handle to an [`ExternalCommand`] started by [`ExternalCommand::spawn()`].
+///
+/// The handle implements [`AsFd`](std::os::fd::AsFd) so that it can be registered with
+/// `poll(2)` or a similar mechanism, e.g., alongside the terminal input. The descriptor
+/// becomes readable (end of file) once the command has closed its stdout and stderr and
+/// its stdin has been written, which normally happens when it exits. After that,
+/// [`RunningCommand::wait()`] does not block on the command's I/O.
+#[derive(Debug)]
+pub struct RunningCommand {
+    command: ExternalCommand,
+    child: std::process::Child,
+    stdin: Option<JoinHandle<std::io::Result<()>>>,
+    stdout: JoinHandle<std::io::Result<Vec<u8>>>,
+    stderr: JoinHandle<std::io::Result<Vec<u8>>>,
+    ready: std::io::PipeReader,
+}
+
+impl RunningCommand {
+    /// Returns the OS-assigned process identifier of the command.
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Returns the exit status if the command has finished, without blocking.
+    pub fn try_wait(&mut self) -> Result<Option<std::process::ExitStatus>, CommandError> {
+        self.child.try_wait().map_err(|error| CommandError::Wait {
+            command: self.command.command.clone(),
+            error,
+        })
+    }
+
+    /// Forcibly terminates the command.
+    pub fn kill(&mut self) -> std::io::Result<()> {
+        self.child.kill()
+    }
+
+    /// Waits for the command to finish and processes stdout/stderr according to
+    /// the configured output settings.
+    pub fn wait(mut self) -> Result<ExternalCommandOutput, CommandError> {
+        let command = || self.command.command.clone();
+        let status = self.child.wait().map_err(|error| CommandError::Wait {
+            command: command(),
+            error,
+        })?;
+        if let Some(stdin) = self.stdin {
+            join_io_thread(stdin).map_err(|error| CommandError::Stdin {
+                command: command(),
+                error,
+            })?;
+        }
+        let mut stdout_bytes =
+            join_io_thread(self.stdout).map_err(|error| CommandError::Stdout {
+                command: command(),
+                error,
+            })?;
+        let stderr_bytes = join_io_thread(self.stderr).map_err(|error| CommandError::Stderr {
+            command: command(),
+            error,
+        })?;
+
+        let success = status.success();
+        if matches!(self.command.stderr, CommandOutput::MergeWithStdout) {
+            stdout_bytes.extend_from_slice(&stderr_bytes);
+        }
+
+        let base_dir = self.command.current_dir.as_deref();
+        let stdout = self
+            .command
+            .stdout
+            .handle_output(&stdout_bytes, success, base_dir)
+            .map_err(|error| CommandError::Stdout {
+                command: command(),
+                error,
+            })?;
+        let stderr = self
+            .command
+            .stderr
+            .handle_output(&stderr_bytes, success, base_dir)
+            .map_err(|error| CommandError::Stderr {
+                command: command(),
+                error,
+            })?;
+
+        Ok(ExternalCommandOutput {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+}
+
+impl std::os::fd::AsFd for RunningCommand {
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        std::os::fd::AsFd::as_fd(&self.ready)
+    }
+}
+
+impl std::os::fd::AsRawFd for RunningCommand {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        std::os::fd::AsRawFd::as_raw_fd(&self.ready)
+    }
+}
+
+fn spawn_reader<R>(
+    reader: Option<R>,
+    ready_writer: Arc<std::io::PipeWriter>,
+) -> JoinHandle<std::io::Result<Vec<u8>>>
+where
+    R: 'static + Read + Send,
+{
+    std::thread::spawn(move || {
+        let _ready_writer = ready_writer;
+        let mut buf = Vec::new();
+        if let Some(mut reader) = reader {
+            reader.read_to_end(&mut buf)?;
+        }
+        Ok(buf)
+    })
+}
+
+fn join_io_thread<T>(handle: JoinHandle<std::io::Result<T>>) -> std::io::Result<T> {
+    handle
+        .join()
+        .unwrap_or_else(|_| Err(std::io::Error::other("I/O thread panicked")))
+}
+
+/// How [`ExternalCommand::with_vars()`] handles placeholders with no corresponding variable.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderPolicy {
+    /// Leave the placeholder as-is (default)
+    #[default]
+    Keep,
+
+    /// Replace the placeholder with an empty string
+    Remove,
+
+    /// Fail with an [`UnknownPlaceholderError`]
+    Error,
+}
+
+/// Error returned when a placeholder has no value and [`PlaceholderPolicy::Error`] is used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownPlaceholderError {
+    /// Name of the unknown placeholder
+    pub name: String,
+}
+
+impl std::fmt::Display for UnknownPlaceholderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown placeholder: {{{}}}", self.name)
+    }
+}
+
+impl std::error::Error for UnknownPlaceholderError {}
+
+fn substitute_placeholders(
+    s: &str,
+    vars: &BTreeMap<String, String>,
+    policy: PlaceholderPolicy,
+) -> Result<String, UnknownPlaceholderError> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('{') {
+        result.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        if let Some(after) = rest.strip_prefix('{') {
+            result.push('{');
+            rest = after;
+            continue;
+        }
+
+        let name_len = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+        let name = &rest[..name_len];
+        if name.is_empty() || !rest[name_len..].starts_with('}') {
+            result.push('{');
+            continue;
+        }
+
+        match (vars.get(name), policy) {
+            (Some(value), _) => result.push_str(value),
+            (None, PlaceholderPolicy::Keep) => {
+                result.push('{');
+                result.push_str(&rest[..=name_len]);
+            }
+            (None, PlaceholderPolicy::Remove) => {}
+            (None, PlaceholderPolicy::Error) => {
+                return Err(UnknownPlaceholderError {
+                    name: name.to_owned(),
+                });
+            }
+        }
+        rest = &rest[name_len + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Builder for constructing an [`ExternalCommand`] programmatically.
+///
+/// Unspecified settings have the same defaults as when parsing from JSON.
+///
+/// # Examples
+///
+/// ```
+/// let command = mame::command::ExternalCommand::builder("echo")
+///     .arg("hello")
+///     .env("LANG", "C")
+///     .stdout_capture()
+///     .build();
+/// assert_eq!(command.command_line().to_string(), "echo hello");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExternalCommandBuilder {
+    command: ExternalCommand,
+}
+
+impl ExternalCommandBuilder {
+    /// Creates a new builder for a command that runs the given executable.
+    pub fn new<P: Into<PathBuf>>(command: P) -> Self {
+        Self {
+            command: ExternalCommand {
+                command: command.into(),
+                args: Vec::new(),
+                envs: BTreeMap::new(),
+                current_dir: None,
+                expand_globs: false,
+                stdin: CommandInput::default(),
+                stdout: CommandOutput::default(),
+                stderr: CommandOutput::default(),
+            },
+        }
+    }
+
+    /// Sets the executable to run.
+    pub fn command<P: Into<PathBuf>>(mut self, command: P) -> Self {
+        self.command.command = command.into();
+        self
+    }
+
+    /// Appends a command line argument.
+    pub fn arg<S: Into<String>>(mut self, arg: S) -> Self {
+        self.command.args.push(arg.into());
+        self
+    }
+
+    /// Appends multiple command line arguments.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.command.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets an environment variable for the command.
+    pub fn env<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.command.envs.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the working directory for the command.
+    pub fn current_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.command.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Sets whether glob patterns in the arguments are expanded at execution time.
+    pub fn expand_globs(mut self, expand: bool) -> Self {
+        self.command.expand_globs = expand;
+        self
+    }
+
+    /// Sets how stdin is provided to the command.
+    pub fn stdin(mut self, stdin: CommandInput) -> Self {
+        self.command.stdin = stdin;
+        self
+    }
+
+    /// Writes the given text to the command's stdin.
+    pub fn stdin_text<S: Into<String>>(self, text: S) -> Self {
+        self.stdin(CommandInput::Text { text: text.into() })
+    }
+
+    /// Pipes the contents of the given file to the command's stdin.
+    pub fn stdin_file<P: Into<PathBuf>>(self, path: P) -> Self {
+        self.stdin(CommandInput::File { path: path.into() })
+    }
+
+    /// Sets how the command's stdout is handled.
+    pub fn stdout(mut self, stdout: CommandOutput) -> Self {
+        self.command.stdout = stdout;
+        self
+    }
+
+    /// Writes the command's stdout to the given file, truncating it first.
+    pub fn stdout_file<P: Into<PathBuf>>(self, path: P) -> Self {
+        self.stdout(CommandOutput::file(path.into()))
+    }
+
+    /// Captures the command's stdout into [`ExternalCommandOutput::stdout`].
+    pub fn stdout_capture(self) -> Self {
+        self.stdout(CommandOutput::capture())
+    }
+
+    /// Sets how the command's stderr is handled.
+    pub fn stderr(mut self, stderr: CommandOutput) -> Self {
+        self.command.stderr = stderr;
+        self
+    }
+
+    /// Writes the command's stderr to the given file, truncating it first.
+    pub fn stderr_file<P: Into<PathBuf>>(self, path: P) -> Self {
+        self.stderr(CommandOutput::file(path.into()))
+    }
+
+    /// Captures the command's stderr into [`ExternalCommandOutput::stderr`].
+    pub fn stderr_capture(self) -> Self {
+        self.stderr(CommandOutput::capture())
+    }
+
+    /// Builds the [`ExternalCommand`].
+    pub fn build(self) -> ExternalCommand {
+        self.command
+    }
+}
+
+impl nojson::DisplayJson for ExternalCommand {
+    fn fmt(&self, f: &mut nojson::JsonFormatter<'_, '_>) -> std::fmt::Result {
+        f.object(|f| {
+            f.member("command", json_path(&self.command)?)?;
+            if !self.args.is_empty() {
+                f.member("args", &self.args)?;
+            }
+            if self.expand_globs {
+                f.member("expand-globs", true)?;
+            }
+            CommandSettings::fmt_members(
+                f,
+                &self.envs,
+                self.current_dir.as_deref(),
+                &self.stdin,
+                &self.stdout,
+                &self.stderr,
+            )
+        })
+    }
+}
+
+/// Configuration for executing a shell script with customizable I/O handling.
+///
+/// The script is run as `<shell> -c <script>`. Unless a shell is given explicitly,
+/// `$SHELL` is used, falling back to `/bin/sh` when `SHELL` is not set.
+/// In JSON, this is written as `{"type": "shell", "command": ...}`.
+#[derive(Debug, Clone)]
+pub struct ShellCommand {
+    /// Shell script to execute
+    pub script: String,
+
+    /// Shell to run the script with (`$SHELL` or `/bin/sh` if `None`)
+    pub shell: Option<PathBuf>,
+
+    /// Environment variables to set for the command execution
+    pub envs: BTreeMap<String, String>,
+
+    /// Working directory for the command execution
+    pub current_dir: Option<PathBuf>,
+
+    /// Configuration for handling stdin input
+    pub stdin: CommandInput,
+
+    /// Configuration for handling stdout output
+    pub stdout: CommandOutput,
+
+    /// Configuration for handling stderr output
+    pub stderr: CommandOutput,
+}
+
+impl ShellCommand {
+    /// Executes the shell script with configured I/O handling.
+    ///
+    /// See [`ExternalCommand::execute()`] for details.
+    pub fn execute(&self) -> Result<ExternalCommandOutput, CommandError> {
+        self.to_external_command().execute()
+    }
+
+    /// Starts the shell script without waiting for it to finish.
+    ///
+    /// See [`ExternalCommand::spawn()`] for details.
+    pub fn spawn(&self) -> Result<RunningCommand, CommandError> {
+        self.to_external_command().spawn()
+    }
+
+    /// Describes what executing this shell script would do, without spawning anything.
+    ///
+    /// See [`ExternalCommand::describe()`] for details.
+    pub fn describe(&self) -> ExternalCommandDescription {
+        self.to_external_command().describe()
+    }
+
+    /// Converts this shell script into an equivalent [`ExternalCommand`] invoking the shell.
+    pub fn to_external_command(&self) -> ExternalCommand {
+        let shell = self.shell.clone().unwrap_or_else(|| {
+            std::env::var_os("SHELL")
+                .filter(|s| !s.is_empty())
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("/bin/sh"))
+        });
+        ExternalCommand {
+            command: shell,
+            args: vec!["-c".to_owned(), self.script.clone()],
+            envs: self.envs.clone(),
+            current_dir: self.current_dir.clone(),
+            expand_globs: false,
+            stdin: self.stdin.clone(),
+            stdout: self.stdout.clone(),
+            stderr: self.stderr.clone(),
+        }
+    }
+}
+
+impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for ShellCommand {
+    type Error = nojson::JsonParseError;
+
+    fn try_from(value: nojson::RawJsonValue<'text, 'raw>) -> Result<Self, Self::Error> {
+        let ty = value.to_member("type")?.required()?;
+        if ty.to_unquoted_string_str()? != "shell" {
+            return Err(ty.invalid("expected \"shell\" command type"));
+        }
+
+        let settings = CommandSettings::try_from(value)?;
+        Ok(Self {
+            script: json::parse_from_flattened_string(value.to_member("command")?.required()?)?,
+            shell: value
+                .to_member("shell")?
+                .map(json::parse_from_flattened_string)?,
+            envs: settings.envs,
+            current_dir: settings.current_dir,
+            stdin: settings.stdin,
+            stdout: settings.stdout,
+            stderr: settings.stderr,
+        })
+    }
+}
+
+impl nojson::DisplayJson for ShellCommand {
+    fn fmt(&self, f: &mut nojson::JsonFormatter<'_, '_>) -> std::fmt::Result {
+        f.object(|f| {
+            f.member("type", "shell")?;
+            f.member("command", &self.script)?;
+            if let Some(shell) = &self.shell {
+                f.member("shell", json_path(shell)?)?;
+            }
+            CommandSettings::fmt_members(
+                f,
+                &self.envs,
+                self.current_dir.as_deref(),
+                &self.stdin,
+                &self.stdout,
+                &self.stderr,
+            )
+        })
+    }
+}
+
+/// Result of executing an [`ExternalCommand`].
+#[derive(Debug, Clone)]
+pub struct ExternalCommandOutput {
+    /// Exit status of the command
+    pub status: std::process::ExitStatus,
+
+    /// Captured stdout text (only set when stdout is configured as [`CommandOutput::Capture`])
+    pub stdout: Option<String>,
+
+    /// Captured stderr text (only set when stderr is configured as [`CommandOutput::Capture`])
+    pub stderr: Option<String>,
+}
+
+/// Errors that can occur when executing an [`ExternalCommand`].
+#[derive(Debug)]
+pub enum CommandError {
+    /// The command could not be started.
+    Spawn {
+        /// The command that failed
+        command: PathBuf,
+        /// The underlying I/O error
+        error: std::io::Error,
+    },
+    /// Writing the configured input to the command's stdin failed.
+    Stdin {
+        /// The command that failed
+        command: PathBuf,
+        /// The underlying I/O error
+        error: std::io::Error,
+    },
+    /// Waiting for the command to finish failed.
+    Wait {
+        /// The command that failed
+        command: PathBuf,
+        /// The underlying I/O error
+        error: std::io::Error,
+    },
+    /// Handling the command's stdout according to its configuration failed.
+    Stdout {
+        /// The command that failed
+        command: PathBuf,
+        /// The underlying I/O error
+        error: std::io::Error,
+    },
+    /// Handling the command's stderr according to its configuration failed.
+    Stderr {
+        /// The command that failed
+        command: PathBuf,
+        /// The underlying I/O error
+        error: std::io::Error,
+    },
+}
+
+impl CommandError {
+    /// Returns the command that failed.
+    pub fn command(&self) -> &Path {
+        match self {
+            Self::Spawn { command, .. }
+            | Self::Stdin { command, .. }
+            | Self::Wait { command, .. }
+            | Self::Stdout { command, .. }
+            | Self::Stderr { command, .. } => command,
+        }
+    }
+
+    /// Returns the underlying I/O error.
+    pub fn io_error(&self) -> &std::io::Error {
+        match self {
+            Self::Spawn { error, .. }
+            | Self::Stdin { error, .. }
+            | Self::Wait { error, .. }
+            | Self::Stdout { error, .. }
+            | Self::Stderr { error, .. } => error,
+        }
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = self.command().display();
+        let error = self.io_error();
+        match self {
+            Self::Spawn { .. } => write!(f, "failed to execute command '{name}': {error}"),
+            Self::Stdin { .. } => write!(f, "failed to write stdin to command '{name}': {error}"),
+            Self::Wait { .. } => write!(f, "failed to wait for command '{name}': {error}"),
+            Self::Stdout { .. } => {
+                write!(f, "failed to handle stdout from command '{name}': {error}")
+            }
+            Self::Stderr { .. } => {
+                write!(f, "failed to handle stderr from command '{name}': {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CommandError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.io_error())
+    }
+}
+
+impl From<CommandError> for std::io::Error {
+    fn from(error: CommandError) -> Self {
+        std::io::Error::new(error.io_error().kind(), error)
+    }
+}
+
 /// Configuration for providing input to a command's stdin.
 #[derive(Debug, Default, Clone)]
 pub enum CommandInput {
@@ -149,10 +949,48 @@ pub enum CommandInput {
         /// Path to the file whose contents will be piped to stdin
         path: PathBuf,
     },
+
+    /// Input streamed from a reader (not available from JSON)
+    ///
+    /// Use [`CommandInput::reader()`] to create this variant.
+    Reader(SharedReader),
+}
+
+/// A reader used by [`CommandInput::Reader`].
+///
+/// Clones share the same underlying reader, so once the input has been consumed by
+/// one command execution, subsequent executions receive only the remaining data.
+#[derive(Clone)]
+pub struct SharedReader(Arc<Mutex<Box<dyn Read + Send>>>);
+
+impl std::fmt::Debug for SharedReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SharedReader").finish_non_exhaustive()
+    }
 }
 
 impl CommandInput {
-    fn handle_input<W: Write>(&self, writer: Option<W>) -> std::io::Result<()> {
+    /// Creates an input that streams the contents of `reader` to stdin.
+    ///
+    /// This avoids loading large inputs into memory as with [`CommandInput::Text`].
+    pub fn reader<R: 'static + Read + Send>(reader: R) -> Self {
+        Self::Reader(SharedReader(Arc::new(Mutex::new(Box::new(reader)))))
+    }
+
+    fn describe(&self, base_dir: Option<&Path>) -> String {
+        match self {
+            Self::Null => "none".to_owned(),
+            Self::Text { text } => format!("text {text:?}"),
+            Self::File { path } => format!("file {}", resolve_path(base_dir, path).display()),
+            Self::Reader(_) => "reader".to_owned(),
+        }
+    }
+
+    fn handle_input<W: Write>(
+        &self,
+        writer: Option<W>,
+        base_dir: Option<&Path>,
+    ) -> std::io::Result<()> {
         let Some(mut writer) = writer else {
             return Ok(());
         };
@@ -162,14 +1000,36 @@ impl CommandInput {
                 writer.write_all(text.as_bytes())?;
             }
             Self::File { path } => {
-                let mut file = std::fs::File::open(path)?;
+                let mut file = std::fs::File::open(resolve_path(base_dir, path))?;
                 std::io::copy(&mut file, &mut writer)?;
             }
+            Self::Reader(SharedReader(reader)) => {
+                let mut reader = reader.lock().unwrap_or_else(|e| e.into_inner());
+                std::io::copy(&mut *reader, &mut writer)?;
+            }
         }
         Ok(())
     }
 }
 
+/// [`CommandInput::Reader`] cannot be represented in JSON, so formatting it fails.
+impl nojson::DisplayJson for CommandInput {
+    fn fmt(&self, f: &mut nojson::JsonFormatter<'_, '_>) -> std::fmt::Result {
+        f.object(|f| match self {
+            Self::Null => f.member("type", "null"),
+            Self::Reader(_) => Err(std::fmt::Error),
+            Self::Text { text } => {
+                f.member("type", "text")?;
+                f.member("text", text)
+            }
+            Self::File { path } => {
+                f.member("type", "file")?;
+                f.member("path", json_path(path)?)
+            }
+        })
+    }
+}
+
 impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for CommandInput {
     type Error = nojson::JsonParseError;
 
@@ -189,7 +1049,7 @@ impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for CommandInput {
 }
 
 /// Configuration for handling command output (stdout/stderr).
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub enum CommandOutput {
     /// Discard the output (default)
     #[default]
@@ -209,12 +1069,100 @@ pub enum CommandOutput {
         /// Skip writing if the command executed successfully (exit code 0)
         skip_if_success: bool,
     },
+
+    /// Merge the output into stdout handling, like `2>&1` in a shell (stderr only)
+    ///
+    /// Both streams are fully collected before being handled, so stderr is
+    /// appended after stdout rather than truly interleaved.
+    MergeWithStdout,
+
+    /// Capture output into [`ExternalCommandOutput`] as text
+    Capture {
+        /// Skip capturing if the output is empty
+        skip_if_empty: bool,
+
+        /// Skip capturing if the command executed successfully (exit code 0)
+        skip_if_success: bool,
+    },
 }
 
 impl CommandOutput {
-    fn handle_output(&self, output: &[u8], success: bool) -> std::io::Result<()> {
+    fn file(path: PathBuf) -> Self {
+        Self::File {
+            path,
+            append: false,
+            skip_if_empty: false,
+            skip_if_success: false,
+        }
+    }
+
+    fn capture() -> Self {
+        Self::Capture {
+            skip_if_empty: false,
+            skip_if_success: false,
+        }
+    }
+
+    fn describe(&self, base_dir: Option<&Path>) -> String {
+        let flags = |flags: &[(bool, &str)]| {
+            let enabled = flags
+                .iter()
+                .filter(|(enabled, _)| *enabled)
+                .map(|(_, name)| *name)
+                .collect::<Vec<_>>();
+            if enabled.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", enabled.join(", "))
+            }
+        };
+        match self {
+            Self::Null => "discard".to_owned(),
+            Self::File {
+                path,
+                append,
+                skip_if_empty,
+                skip_if_success,
+            } => format!(
+                "file {}{}",
+                resolve_path(base_dir, path).display(),
+                flags(&[
+                    (*append, "append"),
+                    (*skip_if_empty, "skip if empty"),
+                    (*skip_if_success, "skip if success"),
+                ])
+            ),
+            Self::MergeWithStdout => "merge with stdout".to_owned(),
+            Self::Capture {
+                skip_if_empty,
+                skip_if_success,
+            } => format!(
+                "capture{}",
+                flags(&[
+                    (*skip_if_empty, "skip if empty"),
+                    (*skip_if_success, "skip if success"),
+                ])
+            ),
+        }
+    }
+
+    fn handle_output(
+        &self,
+        output: &[u8],
+        success: bool,
+        base_dir: Option<&Path>,
+    ) -> std::io::Result<Option<String>> {
         match self {
-            Self::Null => Ok(()),
+            Self::Null | Self::MergeWithStdout => Ok(None),
+            Self::Capture {
+                skip_if_empty,
+                skip_if_success,
+            } => {
+                if (*skip_if_empty && output.is_empty()) || (*skip_if_success && success) {
+                    return Ok(None);
+                }
+                Ok(Some(String::from_utf8_lossy(output).into_owned()))
+            }
             Self::File {
                 path,
                 append,
@@ -222,11 +1170,11 @@ impl CommandOutput {
                 skip_if_success,
             } => {
                 if *skip_if_empty && output.is_empty() {
-                    return Ok(());
+                    return Ok(None);
                 }
 
                 if *skip_if_success && success {
-                    return Ok(());
+                    return Ok(None);
                 }
 
                 let mut file = std::fs::OpenOptions::new()
@@ -234,14 +1182,55 @@ impl CommandOutput {
                     .write(true)
                     .truncate(!*append)
                     .append(*append)
-                    .open(path)?;
+                    .open(resolve_path(base_dir, path))?;
                 file.write_all(output)?;
-                Ok(())
+                Ok(None)
             }
         }
     }
 }
 
+impl nojson::DisplayJson for CommandOutput {
+    fn fmt(&self, f: &mut nojson::JsonFormatter<'_, '_>) -> std::fmt::Result {
+        f.object(|f| match self {
+            Self::Null => f.member("type", "null"),
+            Self::File {
+                path,
+                append,
+                skip_if_empty,
+                skip_if_success,
+            } => {
+                f.member("type", "file")?;
+                f.member("path", json_path(path)?)?;
+                if *append {
+                    f.member("append", true)?;
+                }
+                if *skip_if_empty {
+                    f.member("skip-if-empty", true)?;
+                }
+                if *skip_if_success {
+                    f.member("skip-if-success", true)?;
+                }
+                Ok(())
+            }
+            Self::MergeWithStdout => f.member("type", "merge-with-stdout"),
+            Self::Capture {
+                skip_if_empty,
+                skip_if_success,
+            } => {
+                f.member("type", "capture")?;
+                if *skip_if_empty {
+                    f.member("skip-if-empty", true)?;
+                }
+                if *skip_if_success {
+                    f.member("skip-if-success", true)?;
+                }
+                Ok(())
+            }
+        })
+    }
+}
+
 impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for CommandOutput {
     type Error = nojson::JsonParseError;
 
@@ -264,11 +1253,99 @@ impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for CommandOutput {
                     .map(bool::try_from)?
                     .unwrap_or_default(),
             }),
+            "merge-with-stdout" => Ok(Self::MergeWithStdout),
+            "capture" => Ok(Self::Capture {
+                skip_if_empty: value
+                    .to_member("skip-if-empty")?
+                    .map(bool::try_from)?
+                    .unwrap_or_default(),
+                skip_if_success: value
+                    .to_member("skip-if-success")?
+                    .map(bool::try_from)?
+                    .unwrap_or_default(),
+            }),
             _ => Err(ty.invalid("unknown stdin type")),
         }
     }
 }
 
+fn expand_glob(pattern: &str, base_dir: Option<&Path>) -> Vec<String> {
+    let is_glob = |s: &str| s.contains(['*', '?', '[']);
+    let (dir, file_pattern) = match pattern.rfind('/') {
+        Some(i) => pattern.split_at(i + 1),
+        None => ("", pattern),
+    };
+    if !is_glob(file_pattern) || is_glob(dir) {
+        return Vec::new();
+    }
+
+    let search_dir = resolve_path(base_dir, Path::new(if dir.is_empty() { "." } else { dir }));
+    let Ok(entries) = std::fs::read_dir(search_dir) else {
+        return Vec::new();
+    };
+    let file_pattern_chars = file_pattern.chars().collect::<Vec<_>>();
+    let mut matches = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| !name.starts_with('.') || file_pattern.starts_with('.'))
+        .filter(|name| glob_match(&file_pattern_chars, &name.chars().collect::<Vec<_>>()))
+        .map(|name| format!("{dir}{name}"))
+        .collect::<Vec<_>>();
+    matches.sort();
+    matches
+}
+
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|i| glob_match(rest, &name[i..])),
+        Some(('?', rest)) => !name.is_empty() && glob_match(rest, &name[1..]),
+        Some(('[', rest)) => {
+            // A `]` right after `[` (or `[!`) is part of the set
+            let set_start = usize::from(matches!(rest.first(), Some('!' | '^')));
+            let Some(end) = rest
+                .iter()
+                .skip(set_start + 1)
+                .position(|&c| c == ']')
+                .map(|i| i + set_start + 1)
+            else {
+                // Without a closing bracket, `[` is matched literally
+                return name.first() == Some(&'[') && glob_match(rest, &name[1..]);
+            };
+            let Some((&c, name_rest)) = name.split_first() else {
+                return false;
+            };
+
+            let set = &rest[set_start..end];
+            let mut in_set = false;
+            let mut i = 0;
+            while i < set.len() {
+                if i + 2 < set.len() && set[i + 1] == '-' {
+                    in_set |= (set[i]..=set[i + 2]).contains(&c);
+                    i += 3;
+                } else {
+                    in_set |= set[i] == c;
+                    i += 1;
+                }
+            }
+            in_set != (set_start == 1) && glob_match(&rest[end + 1..], name_rest)
+        }
+        Some((p, rest)) => name.first() == Some(p) && glob_match(rest, &name[1..]),
+    }
+}
+
+fn resolve_path(base_dir: Option<&Path>, path: &Path) -> PathBuf {
+    match base_dir {
+        Some(dir) => dir.join(path),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Returns `path` as a JSON string value, failing instead of replacing non-UTF-8 bytes
+/// so that the path is never written lossily.
+fn json_path(path: &Path) -> Result<&str, std::fmt::Error> {
+    path.to_str().ok_or(std::fmt::Error)
+}
+
 struct CommandLine<'a> {
     command: &'a PathBuf,
     args: &'a [String],
@@ -278,12 +1355,441 @@ impl<'a> std::fmt::Display for CommandLine<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.command.display())?;
         for arg in self.args {
-            if arg.is_empty() || arg.chars().any(|c| c.is_control() || c.is_whitespace()) {
-                write!(f, " {arg:?}")?;
-            } else {
-                write!(f, " {arg}")?;
-            }
+            write!(f, " {}", QuotedArg(arg))?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays an argument as-is, or quoted if it is empty or contains control or whitespace characters.
+struct QuotedArg<'a>(&'a str);
+
+impl std::fmt::Display for QuotedArg<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let arg = self.0;
+        if arg.is_empty() || arg.chars().any(|c| c.is_control() || c.is_whitespace()) {
+            write!(f, "{arg:?}")
+        } else {
+            write!(f, "{arg}")
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write as _;
+    use std::os::unix::ffi::OsStrExt;
+
+    use super::*;
+
+    fn parse_command(text: &str) -> Result<ExternalCommand, nojson::JsonParseError> {
+        let json = nojson::RawJson::parse(text)?;
+        ExternalCommand::try_from(json.value())
+    }
+
+    #[test]
+    fn test_json_round_trip() -> Result<(), nojson::JsonParseError> {
+        let command = parse_command(
+            r#"{
+              "command": "grep",
+              "args": ["-n", ["--", "color"], "pattern with spaces"],
+              "envs": {"LANG": "C", "GREP_COLORS": ["mt=", "01;31"]},
+              "cwd": "/tmp"
+            }"#,
+        )?;
+        let text = nojson::Json(&command).to_string();
+        let reparsed = parse_command(&text)?;
+        assert_eq!(reparsed.command, command.command);
+        assert_eq!(reparsed.args, ["-n", "--color", "pattern with spaces"]);
+        assert_eq!(reparsed.envs, command.envs);
+        assert_eq!(reparsed.current_dir, command.current_dir);
+
+        // Empty args and envs are omitted
+        let command = parse_command(r#"{"command": "ls", "args": [], "envs": {}}"#)?;
+        assert_eq!(nojson::Json(&command).to_string(), r#"{"command":"ls"}"#);
+
+        // Non-UTF-8 paths cannot be written without loss, so formatting fails
+        let path = Path::new(std::ffi::OsStr::from_bytes(b"/tmp/\xff"));
+        let mut text = String::new();
+        let command = ExternalCommand::builder(path).build();
+        assert!(write!(text, "{}", nojson::Json(&command)).is_err());
+        let command = ExternalCommand::builder("ls").current_dir(path).build();
+        assert!(write!(text, "{}", nojson::Json(&command)).is_err());
+        let input = CommandInput::File { path: path.into() };
+        assert!(write!(text, "{}", nojson::Json(&input)).is_err());
+        let output = CommandOutput::file(path.to_path_buf());
+        assert!(write!(text, "{}", nojson::Json(&output)).is_err());
+        let command = ShellCommand {
+            script: "true".to_owned(),
+            shell: Some(path.to_path_buf()),
+            envs: BTreeMap::new(),
+            current_dir: None,
+            stdin: CommandInput::Null,
+            stdout: CommandOutput::Null,
+            stderr: CommandOutput::Null,
+        };
+        assert!(write!(text, "{}", nojson::Json(&command)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_io_json_round_trip() -> Result<(), nojson::JsonParseError> {
+        let inputs = [
+            CommandInput::Null,
+            CommandInput::Text {
+                text: "hello\n".to_owned(),
+            },
+            CommandInput::File {
+                path: PathBuf::from("input.txt"),
+            },
+        ];
+        for input in inputs {
+            let text = nojson::Json(&input).to_string();
+            let json = nojson::RawJson::parse(&text)?;
+            let parsed = CommandInput::try_from(json.value())?;
+            assert_eq!(format!("{parsed:?}"), format!("{input:?}"), "{text}");
+        }
+
+        // Readers have no JSON form
+        let input = CommandInput::reader(std::io::empty());
+        let mut text = String::new();
+        assert!(write!(text, "{}", nojson::Json(&input)).is_err());
+        let command = ExternalCommand::builder("cat").stdin(input).build();
+        assert!(write!(text, "{}", nojson::Json(&command)).is_err());
+
+        let outputs = [
+            CommandOutput::Null,
+            CommandOutput::file(PathBuf::from("out.log")),
+            CommandOutput::File {
+                path: PathBuf::from("out.log"),
+                append: true,
+                skip_if_empty: true,
+                skip_if_success: true,
+            },
+            CommandOutput::MergeWithStdout,
+            CommandOutput::capture(),
+            CommandOutput::Capture {
+                skip_if_empty: true,
+                skip_if_success: false,
+            },
+        ];
+        for output in outputs {
+            let text = nojson::Json(&output).to_string();
+            let json = nojson::RawJson::parse(&text)?;
+            assert_eq!(CommandOutput::try_from(json.value())?, output, "{text}");
+        }
+
+        // Defaulted fields are omitted
+        let text = nojson::Json(CommandOutput::file(PathBuf::from("out.log"))).to_string();
+        assert_eq!(text, r#"{"type":"file","path":"out.log"}"#);
+
+        let command = ExternalCommand::builder("sh")
+            .args(["-c", "cat"])
+            .stdin_file("input.txt")
+            .stdout_capture()
+            .stderr(CommandOutput::MergeWithStdout)
+            .build();
+        let text = nojson::Json(&command).to_string();
+        assert_eq!(parse_command(&text)?, command);
+        Ok(())
+    }
+
+    #[test]
+    fn test_describe() -> Result<(), nojson::JsonParseError> {
+        let command = parse_command(
+            r#"{
+              "command": "grep",
+              "args": ["-n", "foo bar"],
+              "envs": {"LANG": "C", "GREP_COLOR": "01; 31"},
+              "cwd": "/work",
+              "stdin": {"type": "text", "text": "foo bar\nbaz\n"},
+              "stdout": {"type": "file", "path": "out.log", "append": true},
+              "stderr": {"type": "capture", "skip-if-empty": true}
+            }"#,
+        )?;
+        let description = command.describe();
+        assert_eq!(description.command_line, r#"grep -n "foo bar""#);
+        assert_eq!(description.envs, [r#"GREP_COLOR="01; 31""#, "LANG=C"]);
+        assert_eq!(description.stdin, r#"text "foo bar\nbaz\n""#);
+        assert_eq!(description.stdout, "file /work/out.log (append)");
+        assert_eq!(description.stderr, "capture (skip if empty)");
+        assert_eq!(
+            description.to_string(),
+            r#"command: grep -n "foo bar"
+env: GREP_COLOR="01; 31"
+env: LANG=C
+cwd: /work
+stdin: text "foo bar\nbaz\n"
+stdout: file /work/out.log (append)
+stderr: capture (skip if empty)
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_glob_match() {
+        let glob_match = |pattern: &str, name: &str| {
+            let chars = |s: &str| s.chars().collect::<Vec<_>>();
+            super::glob_match(&chars(pattern), &chars(name))
+        };
+        assert!(glob_match("*.rs", "main.rs"));
+        assert!(glob_match("*.rs", ".rs"));
+        assert!(!glob_match("*.rs", "main.rsx"));
+        assert!(glob_match("?a*", "bar"));
+        assert!(!glob_match("?a*", "a"));
+        assert!(glob_match("[a-c]x", "bx"));
+        assert!(!glob_match("[!a-c]x", "bx"));
+        assert!(glob_match("[]]", "]"));
+        assert!(glob_match("[x", "[x"));
+    }
+
+    #[test]
+    fn test_expand_globs() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().join(format!("mame-glob-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub"))?;
+        for name in ["b.rs", "a.rs", "c.txt", ".hidden.rs", "sub/d.rs"] {
+            std::fs::write(dir.join(name), "")?;
+        }
+
+        let command = parse_command(&format!(
+            r#"{{
+              "command": "echo",
+              "args": ["*.rs", "sub/*.rs", "*.none", "plain"],
+              "cwd": {},
+              "expand-globs": true,
+              "stdout": {{"type": "capture"}}
+            }}"#,
+            nojson::Json(dir.display().to_string())
+        ))?;
+        let output = command.execute()?;
+        assert_eq!(
+            output.stdout.as_deref(),
+            Some("a.rs b.rs sub/d.rs *.none plain\n")
+        );
+        assert_eq!(
+            command.describe().command_line,
+            "echo a.rs b.rs sub/d.rs *.none plain"
+        );
+
+        // Not expanded by default
+        let command = ExternalCommand {
+            expand_globs: false,
+            ..command
+        };
+        assert_eq!(command.expanded_args(), command.args);
+        assert_eq!(
+            command.describe().command_line,
+            "echo *.rs sub/*.rs *.none plain"
+        );
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_capture_output() -> Result<(), Box<dyn std::error::Error>> {
+        let command = parse_command(
+            r#"{
+              "command": "sh",
+              "args": ["-c", "printf out; printf err >&2; exit 1"],
+              "stdout": {"type": "capture"},
+              "stderr": {"type": "capture", "skip-if-empty": true}
+            }"#,
+        )?;
+        let output = command.execute()?;
+        assert!(!output.status.success());
+        assert_eq!(output.stdout.as_deref(), Some("out"));
+        assert_eq!(output.stderr.as_deref(), Some("err"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder() -> Result<(), Box<dyn std::error::Error>> {
+        let script = r#"printf '%s:' "$GREETING"; cat"#;
+        let parsed = parse_command(&format!(
+            r#"{{
+              "command": "sh",
+              "args": ["-c", {}],
+              "envs": {{"GREETING": "hello"}},
+              "stdin": {{"type": "text", "text": "world"}},
+              "stdout": {{"type": "capture"}}
+            }}"#,
+            nojson::Json(script)
+        ))?;
+        let built = ExternalCommand::builder("sh")
+            .args(["-c", script])
+            .env("GREETING", "hello")
+            .stdin_text("world")
+            .stdout_capture()
+            .build();
+        assert_eq!(
+            built.command_line().to_string(),
+            parsed.command_line().to_string()
+        );
+
+        let parsed_output = parsed.execute()?;
+        let built_output = built.execute()?;
+        assert_eq!(built_output.stdout.as_deref(), Some("hello:world"));
+        assert_eq!(built_output.stdout, parsed_output.stdout);
+        assert_eq!(built_output.status, parsed_output.status);
+        Ok(())
+    }
+
+    #[test]
+    fn test_execute_with_vars() -> Result<(), Box<dyn std::error::Error>> {
+        let command = parse_command(
+            r#"{
+              "command": "sh",
+              "args": ["-c", "printf '%s|%s|' \"$1\" \"$2\"; cat", "sh", "{query}", "{{query}"],
+              "stdin": {"type": "text", "text": "q={query} {unknown}"},
+              "stdout": {"type": "capture"}
+            }"#,
+        )?;
+        let vars = [("query".to_owned(), "foo bar".to_owned())]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        let output = command.execute_with(&vars)?;
+        assert_eq!(
+            output.stdout.as_deref(),
+            Some("foo bar|{query}|q=foo bar {unknown}")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_placeholder_policy() {
+        let vars = [("a".to_owned(), "1".to_owned())]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        let substitute = |s, policy| substitute_placeholders(s, &vars, policy);
+
+        let s = "{a} {b} {awk $1} {} {{a}";
+        assert_eq!(
+            substitute(s, PlaceholderPolicy::Keep),
+            Ok("1 {b} {awk $1} {} {a}".to_owned())
+        );
+        assert_eq!(
+            substitute(s, PlaceholderPolicy::Remove),
+            Ok("1  {awk $1} {} {a}".to_owned())
+        );
+        assert_eq!(
+            substitute(s, PlaceholderPolicy::Error),
+            Err(UnknownPlaceholderError {
+                name: "b".to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn test_spawn_and_poll() -> Result<(), Box<dyn std::error::Error>> {
+        let command = ExternalCommand::builder("sh")
+            .args(["-c", "sleep 0.1; cat; echo done >&2"])
+            .stdin_text("input\n")
+            .stdout_capture()
+            .stderr_capture()
+            .build();
+        let running = command.spawn()?;
+
+        // Block on the readiness descriptor as a poller would
+        let ready = std::os::fd::AsFd::as_fd(&running).try_clone_to_owned()?;
+        let mut buf = Vec::new();
+        std::fs::File::from(ready).read_to_end(&mut buf)?;
+        assert!(buf.is_empty());
+
+        let output = running.wait()?;
+        assert!(output.status.success());
+        assert_eq!(output.stdout.as_deref(), Some("input\n"));
+        assert_eq!(output.stderr.as_deref(), Some("done\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stdin_reader() -> Result<(), Box<dyn std::error::Error>> {
+        const SIZE: u64 = 5 * 1024 * 1024;
+        let command = ExternalCommand::builder("cat")
+            .stdin(CommandInput::reader(std::io::repeat(b'x').take(SIZE)))
+            .stdout_capture()
+            .build();
+        let output = command.execute()?;
+        assert!(output.status.success());
+        assert_eq!(output.stdout.map(|s| s.len() as u64), Some(SIZE));
+        Ok(())
+    }
+
+    #[test]
+    fn test_spawn_error() -> Result<(), nojson::JsonParseError> {
+        let command = parse_command(r#"{"command": "/nonexistent/mame-test-command"}"#)?;
+        let error = command.execute().expect_err("spawn failure");
+        assert!(matches!(error, CommandError::Spawn { .. }), "{error:?}");
+        assert_eq!(error.command(), Path::new("/nonexistent/mame-test-command"));
+        assert_eq!(error.io_error().kind(), std::io::ErrorKind::NotFound);
+        Ok(())
+    }
+
+    #[test]
+    fn test_shell_command() -> Result<(), Box<dyn std::error::Error>> {
+        let json = nojson::RawJson::parse(
+            r#"{
+              "type": "shell",
+              "shell": "/bin/sh",
+              "command": ["printf 'b\\na\\n'", " | sort"],
+              "stdout": {"type": "capture"}
+            }"#,
+        )?;
+        let command = ShellCommand::try_from(json.value())?;
+        assert_eq!(
+            command.describe().command_line,
+            r#"/bin/sh -c "printf 'b\\na\\n' | sort""#
+        );
+        let output = command.execute()?;
+        assert!(output.status.success());
+        assert_eq!(output.stdout.as_deref(), Some("a\nb\n"));
+
+        let text = nojson::Json(&command).to_string();
+        let reparsed = ShellCommand::try_from(nojson::RawJson::parse(&text)?.value())?;
+        assert_eq!(format!("{reparsed:?}"), format!("{command:?}"));
+
+        let json = nojson::RawJson::parse(
+            r#"{"type": "shell", "shell": "/bin/sh", "command": "exit 3"}"#,
+        )?;
+        let output = ShellCommand::try_from(json.value())?.execute()?;
+        assert_eq!(output.status.code(), Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_current_dir() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = std::env::temp_dir().canonicalize()?;
+        let command = parse_command(&format!(
+            r#"{{"command": "pwd", "cwd": {}, "stdout": {{"type": "capture"}}}}"#,
+            nojson::Json(dir.display().to_string())
+        ))?;
+        let output = command.execute()?;
+        let stdout = output.stdout.expect("captured");
+        assert_eq!(Path::new(stdout.trim()).canonicalize()?, dir);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_stderr_with_stdout() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("mame-merge-{}.log", std::process::id()));
+        let command = parse_command(&format!(
+            r#"{{
+              "command": "sh",
+              "args": ["-c", "echo out; echo err >&2"],
+              "stdout": {{"type": "file", "path": {}}},
+              "stderr": {{"type": "merge-with-stdout"}}
+            }}"#,
+            nojson::Json(path.display().to_string())
+        ))?;
+        let output = command.execute()?;
+        assert!(output.status.success());
+        assert_eq!(output.stderr, None);
+
+        let merged = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(merged, "out\nerr\n");
         Ok(())
     }
 }
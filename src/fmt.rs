@@ -1,4 +1,6 @@
 //! Formatting utilities for terminal UI display elements.
+use std::borrow::Cow;
+
 use crate::matcher::InputMatcher;
 use crate::terminal::str_cols;
 
@@ -35,30 +37,231 @@ impl std::fmt::Display for Padding {
     }
 }
 
-pub(crate) fn horizontal_border(text: &str, width: usize) -> impl std::fmt::Display {
-    HorizontalBorder { text, width }
+/// Creates a displayable string with `text` left-aligned within `width` columns.
+///
+/// The remaining columns are filled with `fill`, which is assumed to be one column wide.
+/// If `text` is wider than `width`, it is truncated at a character boundary; when a wide
+/// character does not fit entirely, the gap is filled with `fill` as well.
+pub fn left_aligned(text: &str, fill: char, width: usize) -> impl '_ + std::fmt::Display {
+    Aligned {
+        text,
+        fill,
+        width,
+        right: false,
+    }
+}
+
+/// Creates a displayable string with `text` right-aligned within `width` columns.
+///
+/// See [`left_aligned()`] for how filling and truncation work.
+pub fn right_aligned(text: &str, fill: char, width: usize) -> impl '_ + std::fmt::Display {
+    Aligned {
+        text,
+        fill,
+        width,
+        right: true,
+    }
+}
+
+#[derive(Debug)]
+struct Aligned<'a> {
+    text: &'a str,
+    fill: char,
+    width: usize,
+    right: bool,
+}
+
+impl std::fmt::Display for Aligned<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = truncate_to_cols(self.text, self.width);
+        let fill = padding(self.fill, self.width.saturating_sub(str_cols(text)));
+        if self.right {
+            write!(f, "{fill}{text}")
+        } else {
+            write!(f, "{text}{fill}")
+        }
+    }
+}
+
+/// Creates a displayable horizontal border line (`─`) of `width` columns with `text`
+/// centered in it.
+///
+/// The text is surrounded by a space on each side. If it does not fit, it is truncated
+/// with `…`, and if `width` is too small to show any text, only the border is drawn.
+pub fn horizontal_border(text: &str, width: usize) -> impl '_ + std::fmt::Display {
+    HorizontalBorder {
+        text,
+        width,
+        right: false,
+        style: None,
+    }
+}
+
+/// Creates a displayable horizontal border line like [`horizontal_border()`], but with
+/// `text` placed at the right end of the border.
+pub fn horizontal_border_right(text: &str, width: usize) -> impl '_ + std::fmt::Display {
+    HorizontalBorder {
+        text,
+        width,
+        right: true,
+        style: None,
+    }
+}
+
+/// Creates a displayable horizontal border line like [`horizontal_border()`], but with
+/// `style` applied to the text only (not to the border).
+///
+/// The style is reset right after the text. If `style` is `None`, this is the same as
+/// [`horizontal_border()`].
+pub fn horizontal_border_with_style(
+    text: &str,
+    width: usize,
+    style: Option<tuinix::TerminalStyle>,
+) -> impl '_ + std::fmt::Display {
+    HorizontalBorder {
+        text,
+        width,
+        right: false,
+        style,
+    }
 }
 
 #[derive(Debug)]
 struct HorizontalBorder<'a> {
     text: &'a str,
     width: usize,
+    right: bool,
+    style: Option<tuinix::TerminalStyle>,
 }
 
 impl<'a> std::fmt::Display for HorizontalBorder<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let text_cols = str_cols(self.text);
-        if 0 < text_cols && text_cols + 2 <= self.width {
-            let padding_needed = self.width - text_cols - 2; // 2 for the spaces around text
-            let left_padding = padding_needed / 2;
-            let right_padding = padding_needed - left_padding;
-
-            write!(f, "{}", padding('─', left_padding))?;
-            write!(f, " {} ", self.text)?;
-            write!(f, "{}", padding('─', right_padding))?;
+        // 2 for the spaces around text, and at least one more column for the text itself
+        if str_cols(self.text) == 0 || self.width < 3 {
+            return write!(f, "{}", padding('─', self.width));
+        }
+
+        let text = truncate_with_ellipsis(self.text, self.width - 2);
+        let padding_needed = (self.width - 2).saturating_sub(str_cols(&text));
+        let left_padding = if self.right {
+            padding_needed
         } else {
-            write!(f, "{}", padding('─', self.width))?;
+            padding_needed / 2
+        };
+        let right_padding = padding_needed - left_padding;
+
+        write!(f, "{}", padding('─', left_padding))?;
+        match &self.style {
+            Some(style) => write!(f, " {style}{text}{} ", tuinix::TerminalStyle::RESET)?,
+            None => write!(f, " {text} ")?,
         }
+        write!(f, "{}", padding('─', right_padding))?;
         Ok(())
     }
 }
+
+/// Truncates `text` to at most `cols` columns, replacing the cut-off part with `…`.
+fn truncate_with_ellipsis(text: &str, cols: usize) -> Cow<'_, str> {
+    if str_cols(text) <= cols {
+        Cow::Borrowed(text)
+    } else if cols == 0 {
+        Cow::Borrowed("")
+    } else {
+        Cow::Owned(format!("{}…", truncate_to_cols(text, cols - 1)))
+    }
+}
+
+/// Returns the longest prefix of `text` that fits within `cols` columns.
+///
+/// Prefixes are measured with [`str_cols()`] rather than by summing per-character widths,
+/// as sequences such as `❤` followed by U+FE0F are wider than their characters combined.
+fn truncate_to_cols(text: &str, cols: usize) -> &str {
+    for (i, c) in text.char_indices() {
+        if str_cols(&text[..i + c.len_utf8()]) > cols {
+            return &text[..i];
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aligned() {
+        assert_eq!(left_aligned("ab", '.', 5).to_string(), "ab...");
+        assert_eq!(right_aligned("ab", '.', 5).to_string(), "...ab");
+
+        // Exact fit
+        assert_eq!(left_aligned("abc", ' ', 3).to_string(), "abc");
+        assert_eq!(right_aligned("日本", ' ', 4).to_string(), "日本");
+
+        // Overflow
+        assert_eq!(left_aligned("abcdef", ' ', 3).to_string(), "abc");
+        assert_eq!(right_aligned("abcdef", ' ', 3).to_string(), "abc");
+        assert_eq!(left_aligned("日本語", '.', 5).to_string(), "日本.");
+        assert_eq!(right_aligned("日本語", '.', 5).to_string(), ".日本");
+        assert_eq!(left_aligned("日本語", '.', 0).to_string(), "");
+
+        // Emoji presentation sequences are wider than the sum of their characters
+        assert_eq!(left_aligned("❤\u{FE0F}", ' ', 1).to_string(), "❤");
+        assert_eq!(left_aligned("❤\u{FE0F}", '.', 3).to_string(), "❤\u{FE0F}.");
+        assert_eq!(right_aligned("a❤\u{FE0F}", '.', 2).to_string(), "a❤");
+    }
+
+    #[test]
+    fn test_horizontal_border() {
+        assert_eq!(horizontal_border("ab", 10).to_string(), "─── ab ───");
+        assert_eq!(horizontal_border("日本", 10).to_string(), "── 日本 ──");
+        assert_eq!(horizontal_border("", 4).to_string(), "────");
+        assert_eq!(horizontal_border("ab", 2).to_string(), "──");
+    }
+
+    #[test]
+    fn test_horizontal_border_right() {
+        assert_eq!(horizontal_border_right("ab", 10).to_string(), "────── ab ");
+        assert_eq!(horizontal_border_right("日本", 9).to_string(), "─── 日本 ");
+        assert_eq!(
+            horizontal_border_right("日本語テキスト", 8).to_string(),
+            "─ 日本… "
+        );
+        assert_eq!(horizontal_border_right("ab", 2).to_string(), "──");
+    }
+
+    #[test]
+    fn test_horizontal_border_with_style() {
+        let bold = tuinix::TerminalStyle::new().bold();
+        let reset = tuinix::TerminalStyle::RESET;
+        assert_eq!(
+            horizontal_border_with_style("Keys", 10, Some(bold)).to_string(),
+            format!("── {bold}Keys{reset} ──")
+        );
+        assert_eq!(
+            horizontal_border_with_style("Keys", 10, None).to_string(),
+            horizontal_border("Keys", 10).to_string()
+        );
+    }
+
+    #[test]
+    fn test_horizontal_border_truncation() {
+        assert_eq!(horizontal_border("abcdefghij", 8).to_string(), " abcde… ");
+        assert_eq!(
+            horizontal_border("日本語テキスト", 8).to_string(),
+            " 日本… ─"
+        );
+        assert_eq!(horizontal_border("abc", 3).to_string(), " … ");
+
+        // Emoji presentation sequences are measured as a whole
+        let hearts = "❤\u{FE0F}❤\u{FE0F}";
+        assert_eq!(horizontal_border(hearts, 4).to_string(), " ❤… ");
+        assert_eq!(
+            horizontal_border(hearts, 6).to_string(),
+            format!(" {hearts} ")
+        );
+        assert_eq!(
+            horizontal_border_right(hearts, 5).to_string(),
+            " ❤\u{FE0F}… "
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! JSON/JSONC utilities.
 use std::borrow::Cow;
 use std::fmt::Write;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 
 /// Flattens a JSON value into a single string.
@@ -51,6 +52,94 @@ pub fn flatten_string<'text, 'raw>(
     }
 }
 
+/// Flattens a JSON value into a single string, inserting `sep` between array elements.
+///
+/// The separator is only inserted between top-level array elements; nested arrays
+/// are concatenated as in [`flatten_string()`]. A single string input is returned as-is.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), nojson::JsonParseError> {
+/// let json = nojson::RawJson::parse(r#"["foo", ["b", "ar"], "baz"]"#)?;
+/// let result = mame::json::flatten_string_with_separator(json.value(), "\n")?;
+/// assert_eq!(result, "foo\nbar\nbaz");
+///
+/// // Single-element array (no trailing separator)
+/// let json = nojson::RawJson::parse(r#"["foo"]"#)?;
+/// let result = mame::json::flatten_string_with_separator(json.value(), "\n")?;
+/// assert_eq!(result, "foo");
+///
+/// // Empty array
+/// let json = nojson::RawJson::parse("[]")?;
+/// let result = mame::json::flatten_string_with_separator(json.value(), "\n")?;
+/// assert_eq!(result, "");
+/// # Ok(())
+/// # }
+/// ```
+pub fn flatten_string_with_separator<'text, 'raw>(
+    value: nojson::RawJsonValue<'text, 'raw>,
+    sep: &str,
+) -> Result<Cow<'text, str>, nojson::JsonParseError> {
+    if let Ok(s) = value.to_unquoted_string_str() {
+        Ok(s)
+    } else if let Ok(array) = value.to_array() {
+        let mut buf = String::new();
+        for (i, value) in array.enumerate() {
+            if i > 0 {
+                buf.push_str(sep);
+            }
+            flatten_string_to_buf(value, &mut buf)?;
+        }
+        Ok(Cow::Owned(buf))
+    } else {
+        Err(value.invalid("expected string or array of strings"))
+    }
+}
+
+/// Flattens a JSON value into a single string, also accepting numbers and booleans.
+///
+/// This behaves like [`flatten_string()`], but JSON numbers and booleans are
+/// rendered using their raw JSON text instead of being rejected.
+/// `null`, objects, and other non-scalar values still produce an error.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), nojson::JsonParseError> {
+/// // Bare number
+/// let json = nojson::RawJson::parse("30")?;
+/// let result = mame::json::flatten_string_lenient(json.value())?;
+/// assert_eq!(result, "30");
+///
+/// // Mixed array
+/// let json = nojson::RawJson::parse(r#"[true, "-x", 5]"#)?;
+/// let result = mame::json::flatten_string_lenient(json.value())?;
+/// assert_eq!(result, "true-x5");
+///
+/// // Objects are still rejected
+/// let json = nojson::RawJson::parse(r#"{"foo": 1}"#)?;
+/// let error = mame::json::flatten_string_lenient(json.value()).unwrap_err();
+/// assert!(error.to_string().contains("expected string or array of strings"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn flatten_string_lenient<'text, 'raw>(
+    value: nojson::RawJsonValue<'text, 'raw>,
+) -> Result<Cow<'text, str>, nojson::JsonParseError> {
+    if let Some(s) = to_lenient_scalar_str(value) {
+        Ok(s)
+    } else if let Ok(array) = value.to_array() {
+        let mut buf = String::new();
+        for value in array {
+            flatten_string_lenient_to_buf(value, &mut buf)?;
+        }
+        Ok(Cow::Owned(buf))
+    } else {
+        Err(value.invalid("expected string or array of strings"))
+    }
+}
+
 /// Parses a JSON value into a type by first flattening it to a string.
 ///
 /// This function combines string flattening with parsing. It first flattens
@@ -87,6 +176,34 @@ where
     s.parse().map_err(|e| value.invalid(e))
 }
 
+/// Parses a JSON value into a type by first flattening it to a string with a separator.
+///
+/// This is the same as [`parse_from_flattened_string()`] except that it uses
+/// [`flatten_string_with_separator()`], which is handy for multi-line text fields
+/// written as an array of lines (pass `"\n"` as `sep`).
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), nojson::JsonParseError> {
+/// let json = nojson::RawJson::parse(r#"["line 1", "line 2"]"#)?;
+/// let result: String = mame::json::parse_from_flattened_string_with_separator(json.value(), "\n")?;
+/// assert_eq!(result, "line 1\nline 2");
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_from_flattened_string_with_separator<T>(
+    value: nojson::RawJsonValue<'_, '_>,
+    sep: &str,
+) -> Result<T, nojson::JsonParseError>
+where
+    T: std::str::FromStr,
+    T::Err: Into<Box<dyn Send + Sync + std::error::Error>>,
+{
+    let s = flatten_string_with_separator(value, sep)?;
+    s.parse().map_err(|e| value.invalid(e))
+}
+
 fn flatten_string_to_buf<'text, 'raw>(
     value: nojson::RawJsonValue<'text, 'raw>,
     buf: &mut String,
@@ -104,7 +221,38 @@ fn flatten_string_to_buf<'text, 'raw>(
     }
 }
 
-pub(crate) fn load_jsonc_file<P: AsRef<Path>, F, T>(path: P, f: F) -> Result<T, LoadJsonError>
+fn flatten_string_lenient_to_buf<'text, 'raw>(
+    value: nojson::RawJsonValue<'text, 'raw>,
+    buf: &mut String,
+) -> Result<(), nojson::JsonParseError> {
+    if let Some(s) = to_lenient_scalar_str(value) {
+        buf.push_str(&s);
+        Ok(())
+    } else if let Ok(array) = value.to_array() {
+        for value in array {
+            flatten_string_lenient_to_buf(value, buf)?;
+        }
+        Ok(())
+    } else {
+        Err(value.invalid("expected string or array of strings"))
+    }
+}
+
+fn to_lenient_scalar_str<'text>(value: nojson::RawJsonValue<'text, '_>) -> Option<Cow<'text, str>> {
+    match value.kind() {
+        nojson::JsonValueKind::String => value.to_unquoted_string_str().ok(),
+        nojson::JsonValueKind::Boolean
+        | nojson::JsonValueKind::Integer
+        | nojson::JsonValueKind::Float => Some(Cow::Borrowed(value.as_raw_str())),
+        _ => None,
+    }
+}
+
+/// Loads a JSONC file, preprocesses it, and converts the resulting JSON value using `f`.
+///
+/// See [`load_jsonc_str()`] for the supported syntax and preprocessing.
+/// Errors include the file path and, for parse errors, the offending position.
+pub fn load_jsonc_file<P: AsRef<Path>, F, T>(path: P, f: F) -> Result<T, LoadJsonError>
 where
     F: for<'text, 'raw> FnOnce(
         nojson::RawJsonValue<'text, 'raw>,
@@ -117,7 +265,46 @@ where
     load_jsonc_str(&path.as_ref().display().to_string(), &text, f)
 }
 
-pub(crate) fn load_jsonc_str<F, T>(name: &str, text: &str, f: F) -> Result<T, LoadJsonError>
+/// Parses a JSONC string, preprocesses it, and converts the resulting JSON value using `f`.
+///
+/// In addition to standard JSON, the following JSONC extensions are accepted:
+/// - Line comments (`// ...`) and block comments (`/* ... */`)
+/// - Trailing commas in arrays and objects
+///
+/// Before `f` is called, every object of the form `{"env!": "NAME", "default": ...}` is
+/// replaced with the value of the environment variable `NAME` (parsed as JSON if possible,
+/// otherwise used as a string), or with `default` if the variable is unset or empty.
+/// The optional `"trim": true` and `"case": "lower" | "upper"` members transform
+/// the environment value, and `"allow-empty": true` makes a variable that is set but
+/// empty produce `""` instead of falling back to `default`.
+///
+/// Within other string values, `${NAME}` is replaced with the value of the environment
+/// variable `NAME` (e.g., `"${HOME}/logs/app.log"`); referencing an undefined variable
+/// is an error. Write `$${` to get a literal `${` (e.g., for shell-style `$${1:-x}`).
+/// Object member names are not interpolated.
+///
+/// `name` is used to identify the input in error messages (e.g., a file name).
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), mame::json::LoadJsonError> {
+/// let text = r#"{
+///   // Comments are allowed
+///   "name": {"env!": "MAME_DOCTEST_UNSET_VAR", "default": "mame"},
+///   "values": [1, 2, 3,], /* and so are trailing commas */
+/// }"#;
+/// let (name, values) = mame::json::load_jsonc_str("example.jsonc", text, |v| {
+///     let name: String = v.to_member("name")?.required()?.try_into()?;
+///     let values: Vec<u32> = v.to_member("values")?.required()?.try_into()?;
+///     Ok((name, values))
+/// })?;
+/// assert_eq!(name, "mame");
+/// assert_eq!(values, [1, 2, 3]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn load_jsonc_str<F, T>(name: &str, text: &str, f: F) -> Result<T, LoadJsonError>
 where
     F: for<'text, 'raw> FnOnce(
         nojson::RawJsonValue<'text, 'raw>,
@@ -166,6 +353,26 @@ impl LoadJsonError {
             error,
         }
     }
+
+    /// Returns the line and column numbers (1-based) where the JSON error occurred.
+    ///
+    /// Returns `None` for [`LoadJsonError::Io`] or if the position cannot be determined.
+    pub fn location(&self) -> Option<(NonZeroUsize, NonZeroUsize)> {
+        match self {
+            Self::Io { .. } => None,
+            Self::Json { text, error, .. } => error.get_line_and_column_numbers(text),
+        }
+    }
+
+    /// Returns the line of text containing the JSON error.
+    ///
+    /// Returns `None` for [`LoadJsonError::Io`] or if the line cannot be determined.
+    pub fn offending_line(&self) -> Option<&str> {
+        match self {
+            Self::Io { .. } => None,
+            Self::Json { text, error, .. } => error.get_line(text),
+        }
+    }
 }
 
 impl std::fmt::Display for LoadJsonError {
@@ -197,7 +404,7 @@ fn format_json_error(
 ) -> std::fmt::Result {
     let (line_num, column_num) = error
         .get_line_and_column_numbers(text)
-        .unwrap_or((std::num::NonZeroUsize::MIN, std::num::NonZeroUsize::MIN));
+        .unwrap_or((NonZeroUsize::MIN, NonZeroUsize::MIN));
 
     let line = error.get_line(text).unwrap_or("");
     let (display_line, display_column) = format_line_around_position(line, column_num.get());
@@ -268,7 +475,13 @@ impl<'text, 'raw> Preprocessor<'text, 'raw> {
 
         if let Some(env_name) = value.to_member("env!").ok().and_then(|v| v.get()) {
             let default_value = value.to_member("default")?.get();
-            self.process_env(env_name, default_value)?;
+            let options = EnvOptions::try_from(value)?;
+            self.process_env(env_name, default_value, options)?;
+            self.last_position = end_position;
+        } else if matches!(value.kind(), nojson::JsonValueKind::String)
+            && value.as_raw_str().contains("${")
+        {
+            self.process_interpolate(value)?;
             self.last_position = end_position;
         } else if let Ok(elements) = value.to_array() {
             for element in elements {
@@ -291,10 +504,11 @@ impl<'text, 'raw> Preprocessor<'text, 'raw> {
         &mut self,
         name: nojson::RawJsonValue<'text, 'raw>,
         default: Option<nojson::RawJsonValue<'text, 'raw>>,
+        options: EnvOptions,
     ) -> Result<(), nojson::JsonParseError> {
         let name_str = name.to_unquoted_string_str()?;
-        let json = if let Ok(value) = std::env::var(name_str.as_ref())
-            && !value.is_empty()
+        let json = if let Ok(value) = std::env::var(name_str.as_ref()).map(|v| options.apply(v))
+            && (options.allow_empty || !value.is_empty())
         {
             nojson::RawJsonOwned::parse(&value)
                 .or_else(|_| nojson::RawJsonOwned::parse(nojson::Json(value).to_string()))
@@ -307,6 +521,120 @@ impl<'text, 'raw> Preprocessor<'text, 'raw> {
         write!(self.processed, "{json}").expect("infallible");
         Ok(())
     }
+
+    fn process_interpolate(
+        &mut self,
+        template: nojson::RawJsonValue<'text, 'raw>,
+    ) -> Result<(), nojson::JsonParseError> {
+        let s = template.to_unquoted_string_str()?;
+        let expanded = interpolate_env(&s).map_err(|(offset, error)| {
+            // Point at the offending `${` unless escape sequences make raw offsets differ
+            let raw = template.as_raw_str();
+            let position = if raw.get(1..raw.len() - 1) == Some(s.as_ref()) {
+                template.position() + 1 + offset
+            } else {
+                template.position()
+            };
+            nojson::JsonParseError::InvalidValue {
+                kind: nojson::JsonValueKind::String,
+                position,
+                error: error.into(),
+            }
+        })?;
+        write!(self.processed, "{}", nojson::Json(expanded)).expect("infallible");
+        Ok(())
+    }
+}
+
+/// Expands `${NAME}` references in a string with the values of environment variables.
+///
+/// `$${` produces a literal `${`, and other `$` characters are left as-is.
+/// On failure, the byte offset of the offending `${` is returned along with the message.
+fn interpolate_env(s: &str) -> Result<String, (usize, String)> {
+    let mut expanded = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('$') {
+        expanded.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let offset = s.len() - rest.len();
+        if let Some(after) = rest.strip_prefix("$${") {
+            expanded.push_str("${");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let Some(end) = after.find('}') else {
+                return Err((offset, format!("unterminated variable reference: {rest:?}")));
+            };
+            let name = &after[..end];
+            let value = std::env::var(name)
+                .map_err(|_| (offset, format!("undefined environment variable: {name:?}")))?;
+            expanded.push_str(&value);
+            rest = &after[end + 1..];
+        } else {
+            expanded.push('$');
+            rest = &rest[1..];
+        }
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Transforms applied to an `env!` value read from the environment.
+///
+/// These are not applied to the `default` value.
+#[derive(Debug, Default, Clone, Copy)]
+struct EnvOptions {
+    trim: bool,
+    allow_empty: bool,
+    case: Option<LetterCase>,
+}
+
+impl EnvOptions {
+    fn apply(self, mut value: String) -> String {
+        if self.trim {
+            value = value.trim().to_owned();
+        }
+        match self.case {
+            Some(LetterCase::Lower) => value.to_lowercase(),
+            Some(LetterCase::Upper) => value.to_uppercase(),
+            None => value,
+        }
+    }
+}
+
+impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for EnvOptions {
+    type Error = nojson::JsonParseError;
+
+    fn try_from(value: nojson::RawJsonValue<'text, 'raw>) -> Result<Self, Self::Error> {
+        Ok(Self {
+            trim: value
+                .to_member("trim")?
+                .map(bool::try_from)?
+                .unwrap_or_default(),
+            allow_empty: value
+                .to_member("allow-empty")?
+                .map(bool::try_from)?
+                .unwrap_or_default(),
+            case: value.to_member("case")?.map(LetterCase::try_from)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LetterCase {
+    Lower,
+    Upper,
+}
+
+impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for LetterCase {
+    type Error = nojson::JsonParseError;
+
+    fn try_from(value: nojson::RawJsonValue<'text, 'raw>) -> Result<Self, Self::Error> {
+        match value.to_unquoted_string_str()?.as_ref() {
+            "lower" => Ok(Self::Lower),
+            "upper" => Ok(Self::Upper),
+            _ => Err(value.invalid("unknown case (expected \"lower\" or \"upper\")")),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -322,4 +650,105 @@ mod tests {
         assert_eq!(preprocessor.processed, "[1, 2, 3]");
         Ok(())
     }
+
+    #[test]
+    fn test_env_preprocessing_with_transforms() -> Result<(), nojson::JsonParseError> {
+        // SAFETY: This environment variable is only used by this test.
+        unsafe {
+            std::env::set_var("TEST_ENVVAR_TRANSFORM_NUMBER", " 42\n");
+            std::env::set_var("TEST_ENVVAR_TRANSFORM_TEXT", "  Hello ");
+        }
+
+        let original_text = r#"[
+          {"env!": "TEST_ENVVAR_TRANSFORM_NUMBER", "trim": true},
+          {"env!": "TEST_ENVVAR_TRANSFORM_TEXT", "trim": true, "case": "lower"},
+          {"env!": "TEST_ENVVAR_TRANSFORM_UNSET", "default": " X ", "trim": true, "case": "lower"}
+        ]"#;
+        let json = nojson::RawJson::parse(original_text)?;
+        let mut preprocessor = Preprocessor::new(&json);
+        preprocessor.process()?;
+
+        let processed = nojson::RawJson::parse(&preprocessor.processed)?;
+        let values = processed.value().to_array()?.collect::<Vec<_>>();
+        assert_eq!(values[0].as_raw_str(), "42");
+        assert_eq!(values[1].as_raw_str(), r#""hello""#);
+        assert_eq!(values[2].as_raw_str(), r#"" X ""#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_env_preprocessing_allow_empty() -> Result<(), nojson::JsonParseError> {
+        // SAFETY: This environment variable is only used by this test.
+        unsafe {
+            std::env::set_var("TEST_ENVVAR_EMPTY", "");
+        }
+
+        let original_text = r#"[
+          {"env!": "TEST_ENVVAR_EMPTY", "default": 1},
+          {"env!": "TEST_ENVVAR_EMPTY", "default": 2, "allow-empty": true},
+          {"env!": "TEST_ENVVAR_EMPTY_UNSET", "default": 3, "allow-empty": true}
+        ]"#;
+        let json = nojson::RawJson::parse(original_text)?;
+        let mut preprocessor = Preprocessor::new(&json);
+        preprocessor.process()?;
+
+        let processed = nojson::RawJson::parse(&preprocessor.processed)?;
+        let values = processed.value().to_array()?.collect::<Vec<_>>();
+        assert_eq!(values[0].as_raw_str(), "1");
+        assert_eq!(values[1].as_raw_str(), r#""""#);
+        assert_eq!(values[2].as_raw_str(), "3");
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_interpolation() -> Result<(), nojson::JsonParseError> {
+        // SAFETY: This environment variable is only used by this test.
+        unsafe {
+            std::env::set_var("TEST_ENVVAR_INTERPOLATION", "/home/\"mame\"");
+        }
+
+        let original_text = r#"["${TEST_ENVVAR_INTERPOLATION}/logs", "$${NOT_EXPANDED} $5", 1]"#;
+        let json = nojson::RawJson::parse(original_text)?;
+        let mut preprocessor = Preprocessor::new(&json);
+        preprocessor.process()?;
+        assert_eq!(
+            preprocessor.processed,
+            r#"["/home/\"mame\"/logs", "${NOT_EXPANDED} $5", 1]"#
+        );
+
+        // Shell-style parameter expansion is not supported and must be escaped
+        let original_text = r#"{"script": "echo ${1:-x}"}"#;
+        let error = load_jsonc_str("test.jsonc", original_text, |_| Ok(()))
+            .expect_err("not a variable name");
+        assert!(error.to_string().contains("\"1:-x\""));
+        let original_text = r#"{"script": "echo $${1:-x}"}"#;
+        let script = load_jsonc_str("test.jsonc", original_text, |v| {
+            v.to_member("script")?.required()?.try_into()
+        });
+        assert_eq!(script.ok(), Some("echo ${1:-x}".to_owned()));
+
+        // The error points at the offending reference rather than the start of the string
+        let original_text = r#"{"path": "~/${TEST_ENVVAR_INTERPOLATION_UNDEFINED}"}"#;
+        let error = load_jsonc_str("test.jsonc", original_text, |_| Ok(()))
+            .expect_err("undefined variable");
+        assert!(
+            error
+                .to_string()
+                .contains("TEST_ENVVAR_INTERPOLATION_UNDEFINED")
+        );
+        let (line, column) = error.location().expect("location");
+        assert_eq!((line.get(), column.get()), (1, 13));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_json_error_location() {
+        let text = "{\n  // comment\n  \"foo\": x\n}";
+        let error = load_jsonc_str("test.jsonc", text, |_| Ok(())).expect_err("invalid JSON");
+
+        let (line, column) = error.location().expect("location");
+        assert_eq!(line.get(), 3);
+        assert_eq!(column.get(), 10);
+        assert_eq!(error.offending_line(), Some("  \"foo\": x"));
+    }
 }
@@ -4,20 +4,27 @@
 //! lists of items (typically input bindings or help text) in a terminal interface.
 //! The rendered legends use Unicode box-drawing characters and automatically
 //! calculate proper sizing based on content width.
+use std::borrow::Cow;
 use std::fmt::Write;
 
-use crate::fmt::horizontal_border;
+use crate::fmt::horizontal_border_with_style;
 use crate::terminal::UnicodeTerminalFrame;
 
 /// A bordered legend box that displays a list of items with a title.
 ///
 /// Renders as a Unicode box with vertical borders containing the items and
 /// a bottom border with the centered title. Automatically sizes to fit content.
+///
+/// If the items do not fit in the frame, they are split into pages and only the
+/// current page (see [`Legend::set_page()`]) is rendered, with a `1/3`-style
+/// indicator appended to the title.
 #[derive(Debug)]
 pub struct Legend<'a> {
     title: &'a str,
     items: Vec<String>,
     size: tuinix::TerminalSize,
+    page: usize,
+    title_style: Option<tuinix::TerminalStyle>,
 }
 
 impl<'a> Legend<'a> {
@@ -27,47 +34,115 @@ impl<'a> Legend<'a> {
         I: Iterator<Item = String>,
     {
         let items = items.collect::<Vec<_>>();
-        let rows = items.len() + 1; // 1 = "─"
-        let border_cols = if title.is_empty() {
-            2 // 2 = "└─"
-        } else {
-            calculate_cols(title) + 4 // 4 = "└ " + " ─"
-        };
-        let cols = std::iter::once(border_cols)
-            .chain(items.iter().map(|x| calculate_cols(x) + 1)) // 1 = "│"
-            .max()
-            .expect("infallible");
-        let size = tuinix::TerminalSize::rows_cols(rows, cols);
-        Self { title, items, size }
+        let size = calculate_size(title, &items);
+        Self {
+            title,
+            items,
+            size,
+            page: 0,
+            title_style: None,
+        }
+    }
+
+    /// Sets the style applied to the title text (e.g., bold), leaving the border unstyled.
+    ///
+    /// By default, the title is rendered without any style.
+    pub fn with_title_style(mut self, style: tuinix::TerminalStyle) -> Self {
+        self.title_style = Some(style);
+        self
     }
 
-    /// Renders the legend to the right edge of the frame if it fits.
+    /// Renders the legend to the top-right corner of the frame.
+    ///
+    /// This is equivalent to [`Legend::render_at()`] with [`LegendPlacement::TopRight`].
     pub fn render(&self, frame: &mut UnicodeTerminalFrame) -> std::fmt::Result {
-        let Some(position) = frame
-            .size()
-            .cols
-            .checked_sub(self.size.cols)
-            .map(tuinix::TerminalPosition::col)
-            .filter(|_| self.size.rows < frame.size().rows)
-        else {
+        self.render_at(frame, LegendPlacement::TopRight)
+    }
+
+    /// Renders the legend at the given placement within the frame.
+    ///
+    /// Only the current page is rendered when the items do not fit in the frame.
+    /// If the legend is wider than the frame, it is placed at the leftmost column.
+    pub fn render_at(
+        &self,
+        frame: &mut UnicodeTerminalFrame,
+        placement: LegendPlacement,
+    ) -> std::fmt::Result {
+        let Some((title, items)) = self.visible_items(frame.size().rows) else {
             return Ok(());
         };
+        let size = calculate_size(&title, items);
+        let position = placement.position(frame.size(), size);
 
-        let mut subframe = UnicodeTerminalFrame::new(self.size);
-        for item in &self.items {
-            writeln!(subframe, "│{item}")?;
-        }
-        writeln!(
-            subframe,
-            "└{}─",
-            horizontal_border(self.title, self.size.cols - 2)
-        )?;
-
+        let mut subframe = UnicodeTerminalFrame::new(size);
+        self.render_lines(&mut subframe, &title, items, size.cols)?;
         frame.draw(position, &subframe);
 
         Ok(())
     }
 
+    fn render_lines<W: Write>(
+        &self,
+        out: &mut W,
+        title: &str,
+        items: &[String],
+        cols: usize,
+    ) -> std::fmt::Result {
+        for item in items {
+            writeln!(out, "│{item}")?;
+        }
+        let border = horizontal_border_with_style(title, cols - 2, self.title_style);
+        writeln!(out, "└{border}─")
+    }
+
+    /// Returns the items of this legend.
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    /// Returns the number of pages needed to render all items in a frame with the given rows.
+    ///
+    /// Returns `0` if the frame is too small to render even a single item.
+    pub fn page_count(&self, frame_rows: usize) -> usize {
+        match items_per_page(frame_rows) {
+            0 => 0,
+            n => self.items.len().div_ceil(n).max(1),
+        }
+    }
+
+    /// Returns the current (0-based) page index.
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// Sets the current (0-based) page index.
+    ///
+    /// The index is clamped to the last page when rendering.
+    pub fn set_page(&mut self, page: usize) {
+        self.page = page;
+    }
+
+    fn visible_items(&self, frame_rows: usize) -> Option<(Cow<'a, str>, &[String])> {
+        let page_count = self.page_count(frame_rows);
+        match page_count {
+            0 => None,
+            1 => Some((Cow::Borrowed(self.title), &self.items)),
+            _ => {
+                let page = self.page.min(page_count - 1);
+                let per_page = items_per_page(frame_rows);
+                let start = page * per_page;
+                let end = (start + per_page).min(self.items.len());
+                let indicator = format!("{}/{page_count}", page + 1);
+                let title = if self.title.is_empty() {
+                    indicator
+                } else {
+                    format!("{} {indicator}", self.title)
+                };
+                Some((Cow::Owned(title), &self.items[start..end]))
+            }
+        }
+    }
+
     /// Returns the size (rows and columns) required to render this legend.
     ///
     /// The size is calculated during construction based on the content width
@@ -77,8 +152,145 @@ impl<'a> Legend<'a> {
     }
 }
 
+/// Placement of a [`Legend`] within a frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LegendPlacement {
+    /// Top-right corner (default)
+    #[default]
+    TopRight,
+
+    /// Top edge, horizontally centered
+    TopCenter,
+
+    /// Top-left corner
+    TopLeft,
+
+    /// Bottom-right corner
+    BottomRight,
+
+    /// Bottom edge, horizontally centered
+    BottomCenter,
+
+    /// Bottom-left corner
+    BottomLeft,
+}
+
+impl LegendPlacement {
+    fn position(
+        self,
+        frame_size: tuinix::TerminalSize,
+        legend_size: tuinix::TerminalSize,
+    ) -> tuinix::TerminalPosition {
+        let free_cols = frame_size.cols.saturating_sub(legend_size.cols);
+        let free_rows = frame_size.rows.saturating_sub(legend_size.rows);
+        let (row, col) = match self {
+            Self::TopRight => (0, free_cols),
+            Self::TopCenter => (0, free_cols / 2),
+            Self::TopLeft => (0, 0),
+            Self::BottomRight => (free_rows, free_cols),
+            Self::BottomCenter => (free_rows, free_cols / 2),
+            Self::BottomLeft => (free_rows, 0),
+        };
+        tuinix::TerminalPosition::row_col(row, col)
+    }
+}
+
+fn items_per_page(frame_rows: usize) -> usize {
+    // The legend must be shorter than the frame, and one row is used by the bottom border
+    frame_rows.saturating_sub(2)
+}
+
+fn calculate_size(title: &str, items: &[String]) -> tuinix::TerminalSize {
+    let rows = items.len() + 1; // 1 = "─"
+    let border_cols = if title.is_empty() {
+        2 // 2 = "└─"
+    } else {
+        calculate_cols(title) + 4 // 4 = "└ " + " ─"
+    };
+    let cols = std::iter::once(border_cols)
+        .chain(items.iter().map(|x| calculate_cols(x) + 1)) // 1 = "│"
+        .max()
+        .expect("infallible");
+    tuinix::TerminalSize::rows_cols(rows, cols)
+}
+
 fn calculate_cols(s: &str) -> usize {
     let mut frame = UnicodeTerminalFrame::new(tuinix::TerminalSize::rows_cols(1, usize::MAX));
     let _ = frame.write_str(s);
     frame.cursor().col
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paging() {
+        let items = (0..7).map(|i| format!("item {i}"));
+        let mut legend = Legend::new("Keys", items);
+
+        assert_eq!(legend.page_count(10), 1);
+        assert_eq!(legend.page_count(5), 3);
+        assert_eq!(legend.page_count(2), 0);
+
+        let (title, items) = legend.visible_items(5).expect("visible");
+        assert_eq!(title, "Keys 1/3");
+        assert_eq!(items, ["item 0", "item 1", "item 2"]);
+
+        legend.set_page(2);
+        let (title, items) = legend.visible_items(5).expect("visible");
+        assert_eq!(title, "Keys 3/3");
+        assert_eq!(items, ["item 6"]);
+
+        // Out-of-range pages are clamped
+        legend.set_page(10);
+        let (title, _) = legend.visible_items(5).expect("visible");
+        assert_eq!(title, "Keys 3/3");
+    }
+
+    #[test]
+    fn test_render_styled_paged_title() -> std::fmt::Result {
+        let bold = tuinix::TerminalStyle::new().bold();
+        let reset = tuinix::TerminalStyle::RESET;
+        let items = (0..7).map(|i| format!("item {i}"));
+        let legend = Legend::new("Keys", items).with_title_style(bold);
+
+        let (title, items) = legend.visible_items(5).expect("visible");
+        let size = calculate_size(&title, items);
+        let mut text = String::new();
+        legend.render_lines(&mut text, &title, items, size.cols)?;
+
+        // Only the title (including the page indicator) is styled, not the border
+        assert_eq!(
+            text.lines().collect::<Vec<_>>(),
+            [
+                "│item 0",
+                "│item 1",
+                "│item 2",
+                &format!("└ {bold}Keys 1/3{reset} ─"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_placement_position() {
+        let frame = tuinix::TerminalSize::rows_cols(24, 80);
+        let legend = tuinix::TerminalSize::rows_cols(5, 20);
+        let position = |placement: LegendPlacement| {
+            let p = placement.position(frame, legend);
+            (p.row, p.col)
+        };
+        assert_eq!(position(LegendPlacement::TopRight), (0, 60));
+        assert_eq!(position(LegendPlacement::TopCenter), (0, 30));
+        assert_eq!(position(LegendPlacement::TopLeft), (0, 0));
+        assert_eq!(position(LegendPlacement::BottomRight), (19, 60));
+        assert_eq!(position(LegendPlacement::BottomCenter), (19, 30));
+        assert_eq!(position(LegendPlacement::BottomLeft), (19, 0));
+
+        // Wider than the frame
+        let legend = tuinix::TerminalSize::rows_cols(5, 100);
+        let p = LegendPlacement::TopRight.position(frame, legend);
+        assert_eq!((p.row, p.col), (0, 0));
+    }
+}
@@ -10,8 +10,5 @@ pub mod fmt;
 pub mod json;
 pub mod legend;
 pub mod preview;
+pub mod style;
 pub mod terminal;
-
-fn io_error(cause: std::io::Error, message: &str) -> std::io::Error {
-    std::io::Error::new(cause.kind(), format!("{message}: {cause}"))
-}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use tuinix::{KeyCode, KeyInput, MouseEvent};
 
 /// Matches terminal input (keyboard and mouse) against specific patterns.
@@ -11,6 +13,9 @@ pub enum InputMatcher {
 
     /// Matches a specific mouse event
     Mouse(MouseEvent),
+
+    /// Never matches any input (useful for disabling a binding)
+    Never,
 }
 
 impl InputMatcher {
@@ -38,68 +43,122 @@ impl InputMatcher {
             }
         }
     }
+
+    /// Returns `true` if every input matched by `self` is also matched by `other`.
+    pub(crate) fn is_covered_by(self, other: InputMatcher) -> bool {
+        match (self, other) {
+            (Self::Never, _) => true,
+            (Self::Key(key), Self::Printable) => other.matches(tuinix::TerminalInput::Key(key)),
+            _ => self == other,
+        }
+    }
+}
+
+/// A precompiled set of [`InputMatcher`]s for fast lookup of the first matching entry.
+///
+/// Literal key and mouse matchers are bucketed into hash maps, so that finding
+/// a match does not require a linear scan over all matchers. The result is the same
+/// as calling [`InputMatcher::matches()`] on each matcher in order.
+#[derive(Debug, Default, Clone)]
+pub struct InputMatcherSet {
+    keys: HashMap<KeyInput, usize>,
+    mouse_events: HashMap<MouseEvent, usize>,
+    printable: Option<usize>,
+}
+
+impl InputMatcherSet {
+    /// Creates a set from matchers, which are identified by their position in `matchers`.
+    pub fn new<I>(matchers: I) -> Self
+    where
+        I: IntoIterator<Item = InputMatcher>,
+    {
+        let mut set = Self::default();
+        for (i, matcher) in matchers.into_iter().enumerate() {
+            // Only the first occurrence is kept since it takes precedence
+            match matcher {
+                InputMatcher::Key(key) => {
+                    set.keys.entry(key).or_insert(i);
+                }
+                InputMatcher::Mouse(event) => {
+                    set.mouse_events.entry(event).or_insert(i);
+                }
+                InputMatcher::Printable => {
+                    set.printable.get_or_insert(i);
+                }
+                InputMatcher::Never => {}
+            }
+        }
+        set
+    }
+
+    /// Returns the position of the first matcher that matches the given input.
+    pub fn find(&self, input: tuinix::TerminalInput) -> Option<usize> {
+        match input {
+            tuinix::TerminalInput::Key(key) => {
+                let literal = self.keys.get(&key).copied();
+                let printable = self
+                    .printable
+                    .filter(|_| InputMatcher::Printable.matches(input));
+                match (literal, printable) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                }
+            }
+            tuinix::TerminalInput::Mouse(m) => self.mouse_events.get(&m.event).copied(),
+        }
+    }
+
+    /// Returns `true` if any matcher in the set matches the given input.
+    pub fn matches_any(&self, input: tuinix::TerminalInput) -> bool {
+        self.find(input).is_some()
+    }
+}
+
+impl FromIterator<InputMatcher> for InputMatcherSet {
+    fn from_iter<I: IntoIterator<Item = InputMatcher>>(iter: I) -> Self {
+        Self::new(iter)
+    }
 }
 
 impl std::str::FromStr for InputMatcher {
-    type Err = String;
+    type Err = InputMatcherParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         // Handle inputs that do not accept modifiers
-        let mouse = |event| InputMatcher::Mouse(event);
         match s {
             "<PRINTABLE>" => return Ok(InputMatcher::Printable),
-            "<LEFTCLICK>" => return Ok(mouse(MouseEvent::LeftPress)),
-            "<LEFTRELEASE>" => return Ok(mouse(MouseEvent::LeftRelease)),
-            "<RIGHTCLICK>" => return Ok(mouse(MouseEvent::RightPress)),
-            "<RIGHTRELEASE>" => return Ok(mouse(MouseEvent::RightRelease)),
-            "<MIDDLECLICK>" => return Ok(mouse(MouseEvent::MiddlePress)),
-            "<MIDDLERELEASE>" => return Ok(mouse(MouseEvent::MiddleRelease)),
-            "<DRAG>" => return Ok(mouse(MouseEvent::Drag)),
-            "<SCROLLUP>" => return Ok(mouse(MouseEvent::ScrollUp)),
-            "<SCROLLDOWN>" => return Ok(mouse(MouseEvent::ScrollDown)),
+            "<NONE>" => return Ok(InputMatcher::Never),
             _ => {}
         }
+        if let Some(&(_, event)) = MOUSE_EVENT_NAMES.iter().find(|(name, _)| *name == s) {
+            return Ok(InputMatcher::Mouse(event));
+        }
 
-        // Handle modifier key combinations like "C-c", "M-x"
+        // Handle modifier key combinations like "C-c", "M-x" (or "Ctrl-c", "Alt-x")
         let mut alt = false;
         let mut ctrl = false;
         let mut remaining = s;
 
-        loop {
-            if let Some(rest) = remaining.strip_prefix("M-")
-                && !alt
-            {
-                remaining = rest;
-                alt = true;
-            } else if let Some(rest) = remaining.strip_prefix("C-")
-                && !ctrl
-            {
-                remaining = rest;
-                ctrl = true;
-            } else {
-                break;
+        while let Some((modifier, rest)) = strip_modifier_prefix(remaining) {
+            let flag = match modifier {
+                Modifier::Ctrl => &mut ctrl,
+                Modifier::Alt => &mut alt,
+            };
+            if *flag {
+                return Err(InputMatcherParseError::ConflictingModifiers(s.to_owned()));
             }
+            *flag = true;
+            remaining = rest;
         }
 
         // Handle special keys
         let key = |code| InputMatcher::Key(KeyInput { ctrl, alt, code });
-        match remaining {
-            "<UP>" => return Ok(key(KeyCode::Up)),
-            "<DOWN>" => return Ok(key(KeyCode::Down)),
-            "<LEFT>" => return Ok(key(KeyCode::Left)),
-            "<RIGHT>" => return Ok(key(KeyCode::Right)),
-            "<ENTER>" => return Ok(key(KeyCode::Enter)),
-            "<ESCAPE>" => return Ok(key(KeyCode::Escape)),
-            "<BACKSPACE>" => return Ok(key(KeyCode::Backspace)),
-            "<TAB>" => return Ok(key(KeyCode::Tab)),
-            "<BACKTAB>" => return Ok(key(KeyCode::BackTab)),
-            "<DELETE>" => return Ok(key(KeyCode::Delete)),
-            "<INSERT>" => return Ok(key(KeyCode::Insert)),
-            "<HOME>" => return Ok(key(KeyCode::Home)),
-            "<END>" => return Ok(key(KeyCode::End)),
-            "<PAGEUP>" => return Ok(key(KeyCode::PageUp)),
-            "<PAGEDOWN>" => return Ok(key(KeyCode::PageDown)),
-            _ => {}
+        if let Some(&(_, code)) = KEY_CODE_NAMES.iter().find(|(name, _)| *name == remaining) {
+            return Ok(key(code));
+        }
+
+        if remaining.is_empty() {
+            return Err(InputMatcherParseError::Empty);
         }
 
         // Handle character input
@@ -117,17 +176,158 @@ impl std::str::FromStr for InputMatcher {
                         let code = KeyCode::Char(ch);
                         Ok(key(code))
                     } else {
-                        Err(format!("invalid Unicode code point: 0x{:x}", code_point))
+                        Err(InputMatcherParseError::InvalidCodePoint(code_point))
                     }
                 }
-                Err(_) => Err(format!("invalid hex notation: {}", remaining)),
+                Err(_) => Err(InputMatcherParseError::InvalidHex(remaining.to_owned())),
             }
         } else {
-            Err(format!("invalid key input format: {s:?}"))
+            Err(InputMatcherParseError::UnknownToken(s.to_owned()))
         }
     }
 }
 
+/// Errors that can occur when parsing an [`InputMatcher`] from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputMatcherParseError {
+    /// The input is not a known key, mouse event, or character
+    UnknownToken(String),
+
+    /// The hex notation (e.g., `0x7f`) could not be parsed as a number
+    InvalidHex(String),
+
+    /// The hex notation does not denote a valid Unicode scalar value
+    InvalidCodePoint(u32),
+
+    /// The same modifier was specified more than once (e.g., `C-Ctrl-x`)
+    ConflictingModifiers(String),
+
+    /// The input is empty, or consists only of modifier prefixes
+    Empty,
+}
+
+impl std::fmt::Display for InputMatcherParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownToken(s) => write!(f, "invalid key input format: {s:?}"),
+            Self::InvalidHex(s) => write!(f, "invalid hex notation: {s}"),
+            Self::InvalidCodePoint(code_point) => {
+                write!(f, "invalid Unicode code point: 0x{code_point:x}")
+            }
+            Self::ConflictingModifiers(s) => write!(f, "duplicate modifier prefix: {s:?}"),
+            Self::Empty => write!(f, "empty key input"),
+        }
+    }
+}
+
+impl std::error::Error for InputMatcherParseError {}
+
+/// Names of the special keys that can be combined with modifiers.
+const KEY_CODE_NAMES: [(&str, KeyCode); 15] = [
+    ("<UP>", KeyCode::Up),
+    ("<DOWN>", KeyCode::Down),
+    ("<LEFT>", KeyCode::Left),
+    ("<RIGHT>", KeyCode::Right),
+    ("<ENTER>", KeyCode::Enter),
+    ("<ESCAPE>", KeyCode::Escape),
+    ("<BACKSPACE>", KeyCode::Backspace),
+    ("<TAB>", KeyCode::Tab),
+    ("<BACKTAB>", KeyCode::BackTab),
+    ("<DELETE>", KeyCode::Delete),
+    ("<INSERT>", KeyCode::Insert),
+    ("<HOME>", KeyCode::Home),
+    ("<END>", KeyCode::End),
+    ("<PAGEUP>", KeyCode::PageUp),
+    ("<PAGEDOWN>", KeyCode::PageDown),
+];
+
+/// Names of the mouse events, which do not accept modifiers.
+const MOUSE_EVENT_NAMES: [(&str, MouseEvent); 9] = [
+    ("<LEFTCLICK>", MouseEvent::LeftPress),
+    ("<LEFTRELEASE>", MouseEvent::LeftRelease),
+    ("<RIGHTCLICK>", MouseEvent::RightPress),
+    ("<RIGHTRELEASE>", MouseEvent::RightRelease),
+    ("<MIDDLECLICK>", MouseEvent::MiddlePress),
+    ("<MIDDLERELEASE>", MouseEvent::MiddleRelease),
+    ("<DRAG>", MouseEvent::Drag),
+    ("<SCROLLUP>", MouseEvent::ScrollUp),
+    ("<SCROLLDOWN>", MouseEvent::ScrollDown),
+];
+
+// The canonical short forms are case-sensitive, while the long aliases are not
+const MODIFIER_PREFIXES: [(&str, Modifier, bool); 5] = [
+    ("C-", Modifier::Ctrl, true),
+    ("M-", Modifier::Alt, true),
+    ("ctrl-", Modifier::Ctrl, false),
+    ("alt-", Modifier::Alt, false),
+    ("meta-", Modifier::Alt, false),
+];
+
+/// Returns a regular expression (ECMA-262 dialect, as used by JSON Schema) that
+/// accepts the trigger strings understood by [`InputMatcher`]'s `FromStr` implementation.
+///
+/// The pattern is built from the same token tables as the parser. It is slightly
+/// more permissive than the parser (e.g., duplicate modifiers are not rejected).
+pub(crate) fn trigger_pattern() -> String {
+    let mut standalone = vec!["<PRINTABLE>", "<NONE>"];
+    standalone.extend(MOUSE_EVENT_NAMES.iter().map(|(name, _)| *name));
+
+    let modifiers = MODIFIER_PREFIXES
+        .iter()
+        .map(|&(prefix, _, case_sensitive)| {
+            if case_sensitive {
+                prefix.to_owned()
+            } else {
+                prefix
+                    .chars()
+                    .map(|c| match (c.to_ascii_uppercase(), c.to_ascii_lowercase()) {
+                        (upper, lower) if upper != lower => format!("[{upper}{lower}]"),
+                        _ => c.to_string(),
+                    })
+                    .collect()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut keys = KEY_CODE_NAMES
+        .iter()
+        .map(|(name, _)| (*name).to_owned())
+        .collect::<Vec<_>>();
+    keys.push("0x[0-9a-fA-F]+".to_owned());
+    keys.push(".".to_owned());
+
+    format!(
+        "^(?:{}|(?:{})*(?:{}))$",
+        standalone.join("|"),
+        modifiers.join("|"),
+        keys.join("|")
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Modifier {
+    Ctrl,
+    Alt,
+}
+
+fn strip_modifier_prefix(s: &str) -> Option<(Modifier, &str)> {
+    MODIFIER_PREFIXES
+        .iter()
+        .find_map(|&(prefix, modifier, case_sensitive)| {
+            let head = s.get(..prefix.len())?;
+            let matched = if case_sensitive {
+                head == prefix
+            } else {
+                head.eq_ignore_ascii_case(prefix)
+            };
+            if matched {
+                Some((modifier, &s[prefix.len()..]))
+            } else {
+                None
+            }
+        })
+}
+
 impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for InputMatcher {
     type Error = nojson::JsonParseError;
 
@@ -143,6 +343,7 @@ impl std::fmt::Display for InputMatcher {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Printable => write!(f, "<PRINTABLE>"),
+            Self::Never => write!(f, "<NONE>"),
             Self::Key(key) => {
                 if key.alt {
                     write!(f, "M-")?;
@@ -191,3 +392,136 @@ impl nojson::DisplayJson for InputMatcher {
         f.string(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modifier_aliases() {
+        let ctrl_alt_x = InputMatcher::Key(KeyInput {
+            ctrl: true,
+            alt: true,
+            code: KeyCode::Char('x'),
+        });
+        for s in [
+            "C-M-x",
+            "Ctrl-Alt-x",
+            "ctrl-meta-x",
+            "Meta-C-x",
+            "CTRL-ALT-x",
+        ] {
+            assert_eq!(s.parse::<InputMatcher>(), Ok(ctrl_alt_x), "{s}");
+        }
+        assert_eq!(ctrl_alt_x.to_string(), "M-C-x");
+
+        assert!("C-Ctrl-x".parse::<InputMatcher>().is_err());
+        assert!("Alt-M-x".parse::<InputMatcher>().is_err());
+    }
+
+    #[test]
+    fn test_trigger_pattern_tokens() {
+        let pattern = trigger_pattern();
+        let names = KEY_CODE_NAMES.iter().map(|(name, _)| *name);
+        let names = names.chain(MOUSE_EVENT_NAMES.iter().map(|(name, _)| *name));
+        for name in names.chain(["<PRINTABLE>", "<NONE>"]) {
+            assert!(pattern.contains(name), "{name}");
+            assert!(name.parse::<InputMatcher>().is_ok(), "{name}");
+        }
+
+        for (prefix, _, case_sensitive) in MODIFIER_PREFIXES {
+            let s = format!("{prefix}<ENTER>");
+            assert!(s.parse::<InputMatcher>().is_ok(), "{s}");
+            if case_sensitive {
+                assert!(pattern.contains(prefix), "{prefix}");
+            } else {
+                let s = format!("{}<ENTER>", prefix.to_uppercase());
+                assert!(s.parse::<InputMatcher>().is_ok(), "{s}");
+                let c = prefix.chars().next().expect("non-empty");
+                let class = format!("[{}{}]", c.to_ascii_uppercase(), c);
+                assert!(pattern.contains(&class), "{prefix}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        use InputMatcherParseError::*;
+
+        let cases = [
+            ("", Empty),
+            ("C-", Empty),
+            ("<FOO>", UnknownToken("<FOO>".to_owned())),
+            ("C-ab", UnknownToken("C-ab".to_owned())),
+            ("0xzz", InvalidHex("0xzz".to_owned())),
+            ("0xd800", InvalidCodePoint(0xd800)),
+            ("C-Ctrl-x", ConflictingModifiers("C-Ctrl-x".to_owned())),
+        ];
+        for (s, expected) in cases {
+            assert_eq!(s.parse::<InputMatcher>(), Err(expected), "{s:?}");
+        }
+    }
+
+    #[test]
+    fn test_matcher_set_equals_linear_scan() {
+        // Simple LCG to get a reproducible pseudo-random keymap
+        let mut state = 12345u64;
+        let mut next = |n: usize| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as usize % n
+        };
+
+        let codes = [
+            KeyCode::Char('a'),
+            KeyCode::Char('b'),
+            KeyCode::Char('Z'),
+            KeyCode::Char('\x7f'),
+            KeyCode::Enter,
+            KeyCode::Up,
+        ];
+        let random_key = |next: &mut dyn FnMut(usize) -> usize| KeyInput {
+            ctrl: next(2) == 0,
+            alt: next(3) == 0,
+            code: codes[next(codes.len())],
+        };
+
+        for _ in 0..50 {
+            let matchers = (0..next(20))
+                .map(|_| match next(10) {
+                    0 => InputMatcher::Printable,
+                    1 => InputMatcher::Never,
+                    2 => InputMatcher::Mouse(MouseEvent::LeftPress),
+                    _ => InputMatcher::Key(random_key(&mut next)),
+                })
+                .collect::<Vec<_>>();
+            let set = matchers.iter().copied().collect::<InputMatcherSet>();
+
+            for _ in 0..50 {
+                let input = tuinix::TerminalInput::Key(random_key(&mut next));
+                let expected = matchers.iter().position(|m| m.matches(input));
+                assert_eq!(set.find(input), expected, "{matchers:?} {input:?}");
+                assert_eq!(set.matches_any(input), expected.is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn test_never() {
+        let never: InputMatcher = "<NONE>".parse().expect("valid");
+        assert_eq!(never, InputMatcher::Never);
+        assert_eq!(never.to_string(), "<NONE>");
+
+        for code in [KeyCode::Char('a'), KeyCode::Char('\x7f'), KeyCode::Enter] {
+            for ctrl in [false, true] {
+                let key = KeyInput {
+                    ctrl,
+                    alt: false,
+                    code,
+                };
+                assert!(!never.matches(tuinix::TerminalInput::Key(key)));
+            }
+        }
+    }
+}
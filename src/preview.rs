@@ -15,11 +15,13 @@
 //! Previews are positioned in the bottom third of the parent terminal region and
 //! automatically adjust their layout based on content size and available space.
 use std::fmt::Write;
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 
 use crate::fmt::{horizontal_border, padding};
-use crate::io_error;
-use crate::terminal::{UnicodeTerminalFrame, str_cols};
+use crate::terminal::{
+    UnicodeTerminalFrame, ansi_escape_len, char_cols, str_cols, str_cols_ignoring_ansi,
+};
 
 /// A dual-pane text preview component for terminal display.
 ///
@@ -60,24 +62,27 @@ impl TextPreview {
 
     fn calculate_pane_regions(&mut self, region: tuinix::TerminalRegion) {
         let pane_region = region.take_bottom(region.size.rows / 3);
-        if self.left_pane.desired_cols() + self.right_pane.desired_cols() <= pane_region.size.cols {
+        let cols = pane_region.size.cols;
+        if self.left_pane.desired_cols() + self.right_pane.desired_cols() <= cols {
+            let left_cols = self.left_pane.desired_cols();
+            let right_cols = self.right_pane.desired_cols();
             self.left_pane.region = pane_region
-                .take_left(self.left_pane.desired_cols())
-                .take_bottom(self.left_pane.desired_rows());
+                .take_left(left_cols)
+                .take_bottom(self.left_pane.desired_rows(left_cols));
             self.right_pane.region = pane_region
-                .take_right(self.right_pane.desired_cols())
-                .take_bottom(self.right_pane.desired_rows());
+                .take_right(right_cols)
+                .take_bottom(self.right_pane.desired_rows(right_cols));
         } else if self.right_pane.is_empty() {
-            self.left_pane.region = pane_region.take_bottom(self.left_pane.desired_rows());
+            self.left_pane.region = pane_region.take_bottom(self.left_pane.desired_rows(cols));
         } else if self.left_pane.is_empty() {
-            self.right_pane.region = pane_region.take_bottom(self.right_pane.desired_rows());
+            self.right_pane.region = pane_region.take_bottom(self.right_pane.desired_rows(cols));
         } else {
             self.left_pane.region = pane_region
-                .take_left(pane_region.size.cols / 2)
-                .take_bottom(self.left_pane.desired_rows());
+                .take_left(cols / 2)
+                .take_bottom(self.left_pane.desired_rows(cols / 2));
             self.right_pane.region = pane_region
-                .take_right(pane_region.size.cols / 2)
-                .take_bottom(self.right_pane.desired_rows());
+                .take_right(cols / 2)
+                .take_bottom(self.right_pane.desired_rows(cols / 2));
         }
     }
 
@@ -101,7 +106,8 @@ impl TextPreview {
 
         let text_region = region.size.to_region().drop_top(1).drop_right(1);
         let mut text_frame = UnicodeTerminalFrame::new(text_region.size);
-        self.left_pane.render_text(&mut text_frame)?;
+        self.left_pane
+            .render_text(text_region.size, &mut text_frame)?;
         frame.draw(text_region.position, &text_frame);
 
         Ok((region.position, frame))
@@ -127,7 +133,8 @@ impl TextPreview {
 
         let text_region = region.size.to_region().drop_top(1).drop_left(1);
         let mut text_frame = UnicodeTerminalFrame::new(text_region.size);
-        self.right_pane.render_text(&mut text_frame)?;
+        self.right_pane
+            .render_text(text_region.size, &mut text_frame)?;
         frame.draw(text_region.position, &text_frame);
 
         Ok((region.position, frame))
@@ -143,13 +150,22 @@ pub struct TextPreviewPane {
     max_cols: usize,
     region: tuinix::TerminalRegion,
     hidden: bool,
+    wrap: bool,
+    line_numbers: bool,
+    first_line: usize,
+    start_line: usize,
+    highlight_line: Option<usize>,
 }
 
 impl TextPreviewPane {
     /// Creates a new text preview pane with the given title and content.
     pub fn new(title: &str, text: &str) -> Self {
         let max_rows = text.lines().count();
-        let max_cols = text.lines().map(str_cols).max().unwrap_or_default();
+        let max_cols = text
+            .lines()
+            .map(str_cols_ignoring_ansi)
+            .max()
+            .unwrap_or_default();
 
         Self {
             title: title.to_owned(),
@@ -158,9 +174,49 @@ impl TextPreviewPane {
             max_cols,
             region: tuinix::TerminalRegion::default(),
             hidden: false,
+            wrap: false,
+            line_numbers: false,
+            first_line: 1,
+            start_line: 1,
+            highlight_line: None,
         }
     }
 
+    /// Enables or disables soft-wrapping of lines wider than the pane.
+    ///
+    /// Wrapping is display-width aware, so wide characters are never split.
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Enables or disables a right-aligned line number gutter.
+    pub fn with_line_numbers(mut self, line_numbers: bool) -> Self {
+        self.line_numbers = line_numbers;
+        self
+    }
+
+    /// Sets the 1-based line number shown at the top of the pane.
+    ///
+    /// The value is clamped to the number of lines in the text.
+    pub fn with_start_line(mut self, line: usize) -> Self {
+        self.start_line = line;
+        self
+    }
+
+    /// Sets the 1-based line number to highlight with a distinct background.
+    pub fn with_highlight_line(mut self, line: Option<usize>) -> Self {
+        self.highlight_line = line;
+        self
+    }
+
+    /// Sets the line number of the first line of the text, for text read from
+    /// the middle of a file.
+    fn with_first_line(mut self, line: usize) -> Self {
+        self.first_line = line;
+        self
+    }
+
     fn hidden() -> Self {
         Self {
             title: String::new(),
@@ -169,6 +225,11 @@ impl TextPreviewPane {
             max_cols: 0,
             region: tuinix::TerminalRegion::default(),
             hidden: true,
+            wrap: false,
+            line_numbers: false,
+            first_line: 1,
+            start_line: 1,
+            highlight_line: None,
         }
     }
 
@@ -176,24 +237,109 @@ impl TextPreviewPane {
         self.max_rows == 0 || self.max_cols == 0
     }
 
-    fn desired_rows(&self) -> usize {
-        self.max_rows + 1
+    fn desired_rows(&self, cols: usize) -> usize {
+        if self.wrap {
+            let text_cols = cols.saturating_sub(self.gutter_cols() + 1); // 1 = "│"
+            self.rows(text_cols).count() + 1
+        } else {
+            self.max_rows - self.skipped_lines() + 1
+        }
+    }
+
+    fn skipped_lines(&self) -> usize {
+        self.start_line.clamp(self.first_line, self.last_line()) - self.first_line
+    }
+
+    fn last_line(&self) -> usize {
+        self.first_line + self.max_rows.max(1) - 1
     }
 
     fn desired_cols(&self) -> usize {
-        self.max_cols.max(str_cols(&self.title) + 3) + 1
+        (self.max_cols + self.gutter_cols()).max(str_cols(&self.title) + 3) + 1
+    }
+
+    fn gutter_cols(&self) -> usize {
+        if self.line_numbers {
+            self.last_line().to_string().len() + 1 // 1 = " "
+        } else {
+            0
+        }
     }
 
     fn title(&self) -> &str {
         &self.title
     }
 
-    fn render_text(&self, frame: &mut UnicodeTerminalFrame) -> std::fmt::Result {
-        for line in self.text.lines().take(frame.size().rows) {
-            writeln!(frame, "{}", line.trim_end())?;
+    fn render_text<W: Write>(&self, size: tuinix::TerminalSize, frame: &mut W) -> std::fmt::Result {
+        let gutter_cols = self.gutter_cols();
+        let text_cols = size.cols.saturating_sub(gutter_cols);
+        let highlight_style = tuinix::TerminalStyle::new().reverse();
+        let mut highlighting = false;
+        for (line_number, line) in self.rows(text_cols).take(size.rows) {
+            match line_number {
+                _ if gutter_cols == 0 => {}
+                Some(n) => write!(frame, "{n:>width$} ", width = gutter_cols - 1)?,
+                None => write!(frame, "{}", padding(' ', gutter_cols))?,
+            }
+            if line_number.is_some() {
+                highlighting = line_number == self.highlight_line;
+            }
+            if highlighting {
+                let fill = padding(' ', text_cols.saturating_sub(str_cols_ignoring_ansi(line)));
+                let reset = tuinix::TerminalStyle::RESET;
+                writeln!(frame, "{highlight_style}{line}{fill}{reset}")?;
+            } else {
+                writeln!(frame, "{line}")?;
+            }
         }
         Ok(())
     }
+
+    /// Returns the display rows, each paired with its 1-based line number
+    /// if the row is the first one of the line.
+    fn rows(&self, cols: usize) -> impl '_ + Iterator<Item = (Option<usize>, &str)> {
+        let skipped_lines = self.skipped_lines();
+        let lines = self.text.lines().enumerate().skip(skipped_lines);
+        lines.flat_map(move |(i, line)| {
+            let line = line.trim_end();
+            let rows = if self.wrap {
+                wrap_line(line, cols)
+            } else {
+                vec![line]
+            };
+            rows.into_iter()
+                .enumerate()
+                .map(move |(j, row)| ((j == 0).then_some(self.first_line + i), row))
+        })
+    }
+}
+
+fn wrap_line(line: &str, cols: usize) -> Vec<&str> {
+    let mut rows = Vec::new();
+    let mut start = 0;
+    let mut width = 0;
+
+    // Rows are split right after the last visible character, so that escape sequences
+    // (which take no columns) move to the next row along with the text they precede
+    let mut visible_end = 0;
+    let mut i = 0;
+    while let Some(c) = line[i..].chars().next() {
+        if let Some(len) = ansi_escape_len(&line[i..]) {
+            i += len;
+            continue;
+        }
+        let w = char_cols(c);
+        if cols < width + w && start < visible_end {
+            rows.push(&line[start..visible_end]);
+            start = visible_end;
+            width = 0;
+        }
+        width += w;
+        i += c.len_utf8();
+        visible_end = i;
+    }
+    rows.push(&line[start..]);
+    rows
 }
 
 /// Configuration for a file preview layout with optional left and right panes.
@@ -228,6 +374,18 @@ impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for FilePreviewSpec
 pub struct FilePreviewPaneSpec {
     /// Path to the file to display in this preview pane
     pub file: PathBuf,
+
+    /// Whether to soft-wrap lines wider than the pane
+    pub wrap: bool,
+
+    /// Whether to show line numbers in a gutter on the left of the text
+    pub line_numbers: bool,
+
+    /// 1-based line number to show at the top of the pane (clamped to the file length)
+    pub start_line: Option<usize>,
+
+    /// 1-based line number to highlight
+    pub highlight_line: Option<usize>,
 }
 
 impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for FilePreviewPaneSpec {
@@ -236,6 +394,16 @@ impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for FilePreviewPane
     fn try_from(value: nojson::RawJsonValue<'text, 'raw>) -> Result<Self, Self::Error> {
         Ok(Self {
             file: value.to_member("file")?.required()?.try_into()?,
+            wrap: value
+                .to_member("wrap")?
+                .map(bool::try_from)?
+                .unwrap_or_default(),
+            line_numbers: value
+                .to_member("line-numbers")?
+                .map(bool::try_from)?
+                .unwrap_or_default(),
+            start_line: value.to_member("start-line")?.map(usize::try_from)?,
+            highlight_line: value.to_member("highlight-line")?.map(usize::try_from)?,
         })
     }
 }
@@ -250,7 +418,7 @@ pub struct FilePreview(TextPreview);
 
 impl FilePreview {
     /// Creates a new file preview from the given specification.
-    pub fn new(spec: &FilePreviewSpec) -> std::io::Result<Self> {
+    pub fn new(spec: &FilePreviewSpec) -> Result<Self, PreviewError> {
         let left_pane = spec
             .left_pane
             .as_ref()
@@ -274,12 +442,16 @@ impl FilePreview {
         self.0.render(frame)
     }
 
-    fn load_text_pane(spec: &FilePreviewPaneSpec) -> std::io::Result<TextPreviewPane> {
-        let content = if !spec.file.exists() {
-            Vec::new()
+    fn load_text_pane(spec: &FilePreviewPaneSpec) -> Result<TextPreviewPane, PreviewError> {
+        let start_line = spec.start_line.unwrap_or(1);
+        let (first_line, content) = if !spec.file.exists() {
+            (1, Vec::new())
         } else {
-            std::fs::read(&spec.file).map_err(|e| {
-                io_error(e, &format!("failed to read file '{}'", spec.file.display()))
+            read_preview_content(&spec.file, start_line).map_err(|error| {
+                PreviewError::ReadFile {
+                    path: spec.file.clone(),
+                    error,
+                }
             })?
         };
 
@@ -290,6 +462,276 @@ impl FilePreview {
             .and_then(|n| n.to_str())
             .unwrap_or_default();
 
-        Ok(TextPreviewPane::new(title, &text))
+        Ok(TextPreviewPane::new(title, &text)
+            .with_first_line(first_line)
+            .with_wrap(spec.wrap)
+            .with_line_numbers(spec.line_numbers)
+            .with_start_line(start_line)
+            .with_highlight_line(spec.highlight_line))
+    }
+}
+
+/// Errors that can occur when loading a [`FilePreview`].
+#[derive(Debug)]
+pub enum PreviewError {
+    /// A file to preview exists but could not be read.
+    ReadFile {
+        /// Path to the file that couldn't be read
+        path: PathBuf,
+        /// The underlying I/O error
+        error: std::io::Error,
+    },
+}
+
+impl std::fmt::Display for PreviewError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReadFile { path, error } => {
+                write!(f, "failed to read file '{}': {error}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for PreviewError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ReadFile { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<PreviewError> for std::io::Error {
+    fn from(error: PreviewError) -> Self {
+        let kind = match &error {
+            PreviewError::ReadFile { error, .. } => error.kind(),
+        };
+        std::io::Error::new(kind, error)
+    }
+}
+
+/// Maximum number of lines loaded for display, starting from the start line.
+const MAX_PREVIEW_LINES: usize = 1000;
+
+/// Maximum number of bytes loaded for display, starting from the start line.
+const MAX_PREVIEW_BYTES: u64 = 1024 * 1024;
+
+/// Reads only the part of a file that a preview pane can display.
+///
+/// Lines before `start_line` are skipped without being buffered (if the file ends before
+/// `start_line`, reading starts at its last line instead), and at most
+/// [`MAX_PREVIEW_LINES`] lines or [`MAX_PREVIEW_BYTES`] bytes are read from there on,
+/// so the last line may be truncated. Returns the line number of the first line read
+/// along with the content.
+fn read_preview_content(path: &Path, start_line: usize) -> std::io::Result<(usize, Vec<u8>)> {
+    let mut reader = BufReader::new(std::fs::File::open(path)?);
+
+    let mut line = 1;
+    let mut line_start = 0;
+    let mut prev_line_start = 0;
+    let mut position = 0;
+    while line < start_line {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            if position == line_start && line > 1 {
+                // The file ends with a newline, so the last line is the previous one
+                line -= 1;
+                line_start = prev_line_start;
+            }
+            reader.seek(SeekFrom::Start(line_start))?;
+            break;
+        }
+
+        let (len, newline) = match buf.iter().position(|&b| b == b'\n') {
+            Some(i) => (i + 1, true),
+            None => (buf.len(), false),
+        };
+        reader.consume(len);
+        position += len as u64;
+        if newline {
+            prev_line_start = line_start;
+            line_start = position;
+            line += 1;
+        }
+    }
+
+    let mut content = Vec::new();
+    let mut reader = reader.take(MAX_PREVIEW_BYTES);
+    for _ in 0..MAX_PREVIEW_LINES {
+        if reader.read_until(b'\n', &mut content)? == 0 {
+            break;
+        }
+    }
+    Ok((line, content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_line() {
+        assert_eq!(wrap_line("abcdefg", 3), ["abc", "def", "g"]);
+        assert_eq!(wrap_line("", 3), [""]);
+
+        // Wide characters are never split across rows
+        assert_eq!(wrap_line("あいう", 5), ["あい", "う"]);
+        assert_eq!(wrap_line("aあいう", 4), ["aあ", "いう"]);
+
+        // Escape sequences take no columns and are never split
+        assert_eq!(
+            wrap_line("\x1b[31mabcdef\x1b[0m", 3),
+            ["\x1b[31mabc", "def\x1b[0m"]
+        );
+        assert_eq!(
+            wrap_line("ab\x1b[1;32mcd\x1b[m", 2),
+            ["ab", "\x1b[1;32mcd\x1b[m"]
+        );
+        assert_eq!(
+            wrap_line("\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\", 2),
+            ["\x1b]8;;https://example.com\x1b\\li", "nk\x1b]8;;\x1b\\"]
+        );
+
+        let pane = TextPreviewPane::new("title", "abcdefg\nあいう").with_wrap(true);
+        assert_eq!(pane.desired_rows(4), 1 + 3 + 3);
+    }
+
+    #[test]
+    fn test_line_number_gutter() {
+        let text = "x\n".repeat(9);
+        let pane = TextPreviewPane::new("", &text).with_line_numbers(true);
+        assert_eq!(pane.gutter_cols(), 2); // "9 "
+        assert_eq!(pane.desired_cols(), 2 + 1 + 1);
+
+        let text = "x\n".repeat(100);
+        let pane = TextPreviewPane::new("", &text).with_line_numbers(true);
+        assert_eq!(pane.gutter_cols(), 4); // "100 "
+        assert_eq!(pane.desired_cols(), 4 + 1 + 1);
+
+        // Wrapped rows only get a line number on their first row
+        let pane = TextPreviewPane::new("", "abcdef")
+            .with_wrap(true)
+            .with_line_numbers(true);
+        assert_eq!(
+            pane.rows(3).collect::<Vec<_>>(),
+            [(Some(1), "abc"), (None, "def")]
+        );
+        assert_eq!(pane.desired_rows(6), 1 + 2);
+    }
+
+    #[test]
+    fn test_highlight_line() -> std::fmt::Result {
+        let style = tuinix::TerminalStyle::new().reverse();
+        let reset = tuinix::TerminalStyle::RESET;
+
+        let pane = TextPreviewPane::new("", "one\ntwo\nthree").with_highlight_line(Some(2));
+        let mut text = String::new();
+        pane.render_text(tuinix::TerminalSize::rows_cols(3, 6), &mut text)?;
+        assert_eq!(
+            text.lines().collect::<Vec<_>>(),
+            ["one", &format!("{style}two   {reset}"), "three"]
+        );
+
+        // Continuation rows of a wrapped line are highlighted as well, after the gutter
+        let pane = TextPreviewPane::new("", "one\nabcdefgh\nthree")
+            .with_wrap(true)
+            .with_line_numbers(true)
+            .with_highlight_line(Some(2));
+        let mut text = String::new();
+        pane.render_text(tuinix::TerminalSize::rows_cols(4, 8), &mut text)?;
+        assert_eq!(
+            text.lines().collect::<Vec<_>>(),
+            [
+                "1 one",
+                &format!("2 {style}abcdef{reset}"),
+                &format!("  {style}gh    {reset}"),
+                "3 three",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_start_line() {
+        let text = (1..=200).map(|i| format!("line {i}\n")).collect::<String>();
+
+        let pane = TextPreviewPane::new("", &text).with_start_line(150);
+        let rows = pane.rows(80).collect::<Vec<_>>();
+        assert_eq!(rows.len(), 51);
+        assert_eq!(rows[0], (Some(150), "line 150"));
+        assert_eq!(rows[50], (Some(200), "line 200"));
+        assert_eq!(pane.desired_rows(80), 1 + 51);
+
+        // Clamped to the last line
+        let pane = TextPreviewPane::new("", &text).with_start_line(500);
+        assert_eq!(pane.rows(80).collect::<Vec<_>>(), [(Some(200), "line 200")]);
+    }
+
+    #[test]
+    fn test_read_file_error() {
+        // A directory can be opened but not read, unlike a missing file which previews as empty
+        let spec = FilePreviewPaneSpec {
+            file: std::env::temp_dir(),
+            wrap: false,
+            line_numbers: false,
+            start_line: None,
+            highlight_line: None,
+        };
+        let error = FilePreview::load_text_pane(&spec).expect_err("unreadable");
+        assert!(
+            matches!(&error, PreviewError::ReadFile { path, .. } if *path == spec.file),
+            "{error:?}"
+        );
+
+        let missing = FilePreviewPaneSpec {
+            file: spec.file.join("mame-missing-file.txt"),
+            ..spec
+        };
+        assert!(FilePreview::load_text_pane(&missing).is_ok());
+    }
+
+    #[test]
+    fn test_read_preview_content() -> std::io::Result<()> {
+        let path = std::env::temp_dir().join(format!("mame-preview-{}.txt", std::process::id()));
+        let line = format!("{}\n", "x".repeat(99));
+        std::fs::write(&path, line.repeat(50_000))?; // 5 MB
+
+        let (first_line, content) = read_preview_content(&path, 1)?;
+        assert_eq!(first_line, 1);
+        assert_eq!(content.len(), MAX_PREVIEW_LINES * line.len());
+
+        // Skipped lines are not kept, and the pane numbers lines from the first one read
+        let (first_line, content) = read_preview_content(&path, 49_990)?;
+        assert_eq!(first_line, 49_990);
+        assert_eq!(content.len(), 11 * line.len());
+        let text = String::from_utf8_lossy(&content);
+        let pane = TextPreviewPane::new("", &text)
+            .with_first_line(first_line)
+            .with_start_line(49_990)
+            .with_line_numbers(true);
+        assert_eq!(pane.rows(100).count(), 11);
+        assert_eq!(pane.rows(100).next(), Some((Some(49_990), line.trim_end())));
+        assert_eq!(pane.gutter_cols(), 6); // "50000 "
+
+        // A huge line before the start line is skipped, not loaded
+        let huge = "x".repeat(2 * MAX_PREVIEW_BYTES as usize);
+        std::fs::write(&path, format!("{huge}\nsecond\n"))?;
+        assert_eq!(read_preview_content(&path, 2)?, (2, b"second\n".to_vec()));
+
+        // Starting past EOF reads the last line so the pane can clamp to it
+        std::fs::write(&path, "first\nsecond\n")?;
+        let (first_line, content) = read_preview_content(&path, 5)?;
+        assert_eq!((first_line, content.as_slice()), (2, &b"second\n"[..]));
+        let text = String::from_utf8_lossy(&content);
+        let pane = TextPreviewPane::new("", &text)
+            .with_first_line(first_line)
+            .with_start_line(5);
+        assert_eq!(pane.rows(100).collect::<Vec<_>>(), [(Some(2), "second")]);
+
+        // Also when the file does not end with a newline
+        std::fs::write(&path, "first\nsecond")?;
+        assert_eq!(read_preview_content(&path, 5)?, (2, b"second".to_vec()));
+
+        std::fs::remove_file(&path)
     }
 }
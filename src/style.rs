@@ -0,0 +1,233 @@
+//! Styling primitives that can be configured from JSON.
+use std::str::FromStr;
+
+const NAMED_COLORS: [(&str, Color); 16] = [
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("white", Color::White),
+    ("bright-black", Color::BrightBlack),
+    ("bright-red", Color::BrightRed),
+    ("bright-green", Color::BrightGreen),
+    ("bright-yellow", Color::BrightYellow),
+    ("bright-blue", Color::BrightBlue),
+    ("bright-magenta", Color::BrightMagenta),
+    ("bright-cyan", Color::BrightCyan),
+    ("bright-white", Color::BrightWhite),
+];
+
+/// A terminal color, either one of the 16 standard ANSI colors or a 24-bit RGB color.
+///
+/// In JSON, a color is written as a string in one of the following forms:
+/// - `"#RRGGBB"` (hex notation, e.g. `"#ff8800"`)
+/// - `"rgb(R, G, B)"` (decimal components in `0..=255`)
+/// - A named color such as `"magenta"` or `"bright-cyan"` (case-insensitive)
+///
+/// Named colors are kept as-is rather than converted to RGB, so that they follow
+/// the terminal's color scheme.
+///
+/// # Examples
+///
+/// ```
+/// use mame::style::Color;
+///
+/// assert_eq!("#ff8800".parse(), Ok(Color::Rgb { r: 255, g: 136, b: 0 }));
+/// assert_eq!("rgb(1, 2, 3)".parse(), Ok(Color::Rgb { r: 1, g: 2, b: 3 }));
+/// assert_eq!("Magenta".parse(), Ok(Color::Magenta));
+/// assert_eq!(Color::Rgb { r: 255, g: 136, b: 0 }.to_string(), "#ff8800");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    /// ANSI black
+    Black,
+    /// ANSI red
+    Red,
+    /// ANSI green
+    Green,
+    /// ANSI yellow
+    Yellow,
+    /// ANSI blue
+    Blue,
+    /// ANSI magenta
+    Magenta,
+    /// ANSI cyan
+    Cyan,
+    /// ANSI white
+    White,
+    /// ANSI bright black (gray)
+    BrightBlack,
+    /// ANSI bright red
+    BrightRed,
+    /// ANSI bright green
+    BrightGreen,
+    /// ANSI bright yellow
+    BrightYellow,
+    /// ANSI bright blue
+    BrightBlue,
+    /// ANSI bright magenta
+    BrightMagenta,
+    /// ANSI bright cyan
+    BrightCyan,
+    /// ANSI bright white
+    BrightWhite,
+    /// 24-bit RGB color
+    Rgb {
+        /// Red component
+        r: u8,
+        /// Green component
+        g: u8,
+        /// Blue component
+        b: u8,
+    },
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseColorError {
+            input: s.to_owned(),
+        };
+
+        if let Some(hex) = s.strip_prefix('#') {
+            if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(error());
+            }
+            let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| error());
+            return Ok(Self::Rgb {
+                r: component(0)?,
+                g: component(2)?,
+                b: component(4)?,
+            });
+        }
+
+        if let Some(args) = s
+            .strip_prefix("rgb(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let mut components = args.split(',').map(|c| c.trim().parse::<u8>());
+            let (Some(Ok(r)), Some(Ok(g)), Some(Ok(b)), None) = (
+                components.next(),
+                components.next(),
+                components.next(),
+                components.next(),
+            ) else {
+                return Err(error());
+            };
+            return Ok(Self::Rgb { r, g, b });
+        }
+
+        NAMED_COLORS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|&(_, color)| color)
+            .ok_or_else(error)
+    }
+}
+
+impl<'text, 'raw> TryFrom<nojson::RawJsonValue<'text, 'raw>> for Color {
+    type Error = nojson::JsonParseError;
+
+    fn try_from(value: nojson::RawJsonValue<'text, 'raw>) -> Result<Self, Self::Error> {
+        value
+            .to_unquoted_string_str()?
+            .parse()
+            .map_err(|e| value.invalid(e))
+    }
+}
+
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rgb { r, g, b } => write!(f, "#{r:02x}{g:02x}{b:02x}"),
+            named => {
+                let (name, _) = NAMED_COLORS
+                    .iter()
+                    .find(|(_, c)| c == named)
+                    .expect("all named colors are listed");
+                write!(f, "{name}")
+            }
+        }
+    }
+}
+
+impl nojson::DisplayJson for Color {
+    fn fmt(&self, f: &mut nojson::JsonFormatter<'_, '_>) -> std::fmt::Result {
+        f.string(self)
+    }
+}
+
+/// Error returned when a string cannot be parsed as a [`Color`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError {
+    input: String,
+}
+
+impl std::fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid color: {:?} (expected \"#RRGGBB\", \"rgb(R, G, B)\", or a color name)",
+            self.input
+        )
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_json(text: &str) -> Result<Color, nojson::JsonParseError> {
+        let json = nojson::RawJson::parse(text)?;
+        Color::try_from(json.value())
+    }
+
+    #[test]
+    fn test_parse_color() -> Result<(), nojson::JsonParseError> {
+        let orange = Color::Rgb {
+            r: 0xff,
+            g: 0x88,
+            b: 0x00,
+        };
+        assert_eq!(parse_json(r##""#FF8800""##)?, orange);
+        assert_eq!(parse_json(r#""rgb(255,136, 0)""#)?, orange);
+        assert_eq!(parse_json(r#""magenta""#)?, Color::Magenta);
+        assert_eq!(parse_json(r#""Bright-Cyan""#)?, Color::BrightCyan);
+
+        for color in [orange, Color::Magenta, Color::BrightCyan] {
+            assert_eq!(color.to_string().parse(), Ok(color));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid_color() {
+        for s in [
+            "#ff880",
+            "#gg8800",
+            "#ff88001",
+            "rgb(256, 0, 0)",
+            "rgb(1, 2)",
+            "purple",
+        ] {
+            assert!(s.parse::<Color>().is_err(), "{s}");
+        }
+
+        let text = r##"{"color": "#12345z"}"##;
+        let json = nojson::RawJson::parse(text).expect("valid JSON");
+        let value = json.value().to_member("color").and_then(|v| v.required());
+        let error = value.and_then(Color::try_from).expect_err("bad hex");
+        assert_eq!(
+            error
+                .get_line_and_column_numbers(text)
+                .map(|(_, c)| c.get()),
+            Some(11)
+        );
+    }
+}
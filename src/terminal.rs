@@ -1,4 +1,7 @@
 //! Unicode-aware terminal utilities for character width calculation and rendering.
+use std::borrow::Cow;
+
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Calculates the display width of a string in terminal columns.
 ///
@@ -19,6 +22,137 @@ pub fn str_cols(s: &str) -> usize {
     unicode_width::UnicodeWidthStr::width(s)
 }
 
+/// Calculates the display width of a string in terminal columns, measuring each
+/// grapheme cluster as a whole.
+///
+/// Unlike [`str_cols()`], which sums the widths of individual characters, this function
+/// treats emoji sequences as a single 2-column glyph, as modern terminals render them:
+/// - ZWJ sequences (e.g., family emoji)
+/// - Sequences with an emoji presentation selector (U+FE0F) or a skin tone modifier
+/// - Regional indicator pairs (flags)
+///
+/// Other clusters, such as a base character followed by combining marks, are measured
+/// as in [`str_cols()`].
+///
+/// # Examples
+///
+/// ```
+/// // Family: man, woman, girl joined with ZWJ
+/// assert_eq!(mame::terminal::grapheme_cols("\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}"), 2);
+/// // Flag of Japan (regional indicators J and P)
+/// assert_eq!(mame::terminal::grapheme_cols("\u{1f1ef}\u{1f1f5}"), 2);
+/// // Heart with emoji presentation selector
+/// assert_eq!(mame::terminal::grapheme_cols("\u{2764}\u{fe0f}"), 2);
+/// assert_eq!(mame::terminal::grapheme_cols("cafe\u{0301}"), 4);
+/// assert_eq!(mame::terminal::grapheme_cols("こんにちは"), 10);
+/// ```
+pub fn grapheme_cols(s: &str) -> usize {
+    s.graphemes(true).map(grapheme_cluster_cols).sum()
+}
+
+fn grapheme_cluster_cols(cluster: &str) -> usize {
+    let mut chars = cluster.chars();
+    let Some(first) = chars.next() else {
+        return 0;
+    };
+    if chars.next().is_none() {
+        return char_cols(first);
+    }
+
+    let is_regional_indicator = |c: char| ('\u{1f1e6}'..='\u{1f1ff}').contains(&c);
+    let is_emoji_sequence_char =
+        |c: char| matches!(c, '\u{200d}' | '\u{fe0f}') || ('\u{1f3fb}'..='\u{1f3ff}').contains(&c);
+    if is_regional_indicator(first) || cluster.chars().any(is_emoji_sequence_char) {
+        2
+    } else {
+        str_cols(cluster)
+    }
+}
+
+/// Calculates the display width of a string in terminal columns, ignoring ANSI escape sequences.
+///
+/// This is the same as [`str_cols()`] applied to the result of [`strip_ansi()`],
+/// which is useful for measuring pre-colored text.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(mame::terminal::str_cols_ignoring_ansi("\x1b[31mHello\x1b[0m"), 5);
+/// assert_eq!(mame::terminal::str_cols_ignoring_ansi("\x1b[1;32mこんにちは\x1b[m"), 10);
+/// assert_eq!(mame::terminal::str_cols_ignoring_ansi("Hello"), 5);
+/// ```
+pub fn str_cols_ignoring_ansi(s: &str) -> usize {
+    str_cols(&strip_ansi(s))
+}
+
+/// Removes ANSI escape sequences from a string.
+///
+/// The following sequences are removed:
+/// - CSI sequences such as SGR colors (`ESC [ ... m`) and cursor movements
+/// - OSC sequences (`ESC ] ...`) terminated by BEL or `ESC \`
+/// - Other two-character escape sequences (`ESC` followed by a single character)
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(mame::terminal::strip_ansi("\x1b[31mred\x1b[0m text"), "red text");
+/// assert_eq!(mame::terminal::strip_ansi("\x1b]0;title\x07body"), "body");
+/// assert_eq!(mame::terminal::strip_ansi("plain"), "plain");
+/// ```
+pub fn strip_ansi(s: &str) -> Cow<'_, str> {
+    if !s.contains('\x1b') {
+        return Cow::Borrowed(s);
+    }
+
+    let mut stripped = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('\x1b') {
+        stripped.push_str(&rest[..i]);
+        rest = &rest[i..];
+        rest = &rest[ansi_escape_len(rest).unwrap_or(rest.len())..];
+    }
+    stripped.push_str(rest);
+    Cow::Owned(stripped)
+}
+
+/// Returns the byte length of the ANSI escape sequence at the start of `s`,
+/// or `None` if `s` does not start with `ESC`.
+///
+/// The recognized sequences are the same as those removed by [`strip_ansi()`].
+pub(crate) fn ansi_escape_len(s: &str) -> Option<usize> {
+    let rest = s.strip_prefix('\x1b')?;
+    let mut chars = rest.char_indices();
+    let len = match chars.next() {
+        Some((_, '[')) => {
+            // Parameter and intermediate bytes are followed by a final byte in 0x40..=0x7e
+            chars
+                .find(|(_, c)| ('\x40'..='\x7e').contains(c))
+                .map_or(rest.len(), |(i, _)| i + 1)
+        }
+        Some((_, ']')) => {
+            let mut end = rest.len();
+            for (i, c) in chars {
+                if c == '\x07' {
+                    end = i + 1;
+                    break;
+                }
+                if c == '\x1b' {
+                    end = if rest[i + 1..].starts_with('\\') {
+                        i + 2
+                    } else {
+                        i + 1
+                    };
+                    break;
+                }
+            }
+            end
+        }
+        Some((_, c)) => c.len_utf8(),
+        None => 0,
+    };
+    Some(1 + len)
+}
+
 /// Calculates the display width of a character in terminal columns.
 ///
 /// This function determines how many columns a single character will occupy